@@ -14,7 +14,7 @@ use hal::clock::GenericClockController;
 use hal::prelude::*;
 use hal::entry;
 use hal::pac::{CorePeripherals, Peripherals};
-use hal::adc::Adc;
+use hal::adc::ADC;
 use cortex_m_semihosting::hprintln;
 
 #[entry]
@@ -29,7 +29,9 @@ fn main() -> ! {
     );
     let mut pins = hal::Pins::new(peripherals.PORT);
     let mut delay = hal::delay::Delay::new(core.SYST, &mut clocks);
-    let mut adc = Adc::adc(peripherals.ADC, &mut peripherals.PM, &mut clocks);
+    let gclk0 = clocks.gclk0();
+    let adc_clock = clocks.adc(&gclk0).unwrap();
+    let mut adc = ADC::init(&mut peripherals.PM, adc_clock, peripherals.ADC);
     let mut a0 = pins.a0.into_function_b(&mut pins.port);
 
     loop {
@@ -1,5 +1,7 @@
 //! Time units
 
+use core::ops::{Add, AddAssign, Sub, SubAssign};
+
 /// Bits per second
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct Bps(pub u32);
@@ -16,6 +18,14 @@ pub struct KiloHertz(pub u32);
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub struct MegaHertz(pub u32);
 
+/// A point in time, in ticks of a monotonic timer since it started
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Instant(pub u64);
+
+/// A span of time, in ticks of a monotonic timer
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub struct Duration(pub u64);
+
 /// Extension trait that adds convenience methods to the `u32` type
 pub trait U32Ext {
     /// Wrap in `Bps`
@@ -66,3 +76,85 @@ impl Into<KiloHertz> for MegaHertz {
         KiloHertz(self.0 * 1_000)
     }
 }
+
+impl Instant {
+    /// The time elapsed from `earlier` to this instant, zero if `earlier`
+    /// is later.
+    pub fn duration_since(self, earlier: Instant) -> Duration {
+        Duration(self.0.saturating_sub(earlier.0))
+    }
+}
+
+impl Add<Duration> for Instant {
+    type Output = Instant;
+
+    fn add(self, duration: Duration) -> Instant {
+        Instant(self.0 + duration.0)
+    }
+}
+
+impl AddAssign<Duration> for Instant {
+    fn add_assign(&mut self, duration: Duration) {
+        self.0 += duration.0;
+    }
+}
+
+impl Sub<Duration> for Instant {
+    type Output = Instant;
+
+    fn sub(self, duration: Duration) -> Instant {
+        Instant(self.0 - duration.0)
+    }
+}
+
+impl SubAssign<Duration> for Instant {
+    fn sub_assign(&mut self, duration: Duration) {
+        self.0 -= duration.0;
+    }
+}
+
+impl Sub<Instant> for Instant {
+    type Output = Duration;
+
+    fn sub(self, earlier: Instant) -> Duration {
+        Duration(self.0 - earlier.0)
+    }
+}
+
+impl Duration {
+    /// The number of ticks of a timer running at `freq` in `ms` milliseconds
+    pub fn from_millis(ms: u32, freq: Hertz) -> Self {
+        Duration(ms as u64 * freq.0 as u64 / 1_000)
+    }
+
+    /// The number of ticks of a timer running at `freq` in `us` microseconds
+    pub fn from_micros(us: u32, freq: Hertz) -> Self {
+        Duration(us as u64 * freq.0 as u64 / 1_000_000)
+    }
+
+    /// This duration in whole milliseconds, for a timer running at `freq`
+    pub fn as_millis(self, freq: Hertz) -> u64 {
+        self.0 * 1_000 / freq.0 as u64
+    }
+
+    /// This duration in whole microseconds, for a timer running at `freq`
+    pub fn as_micros(self, freq: Hertz) -> u64 {
+        self.0 * 1_000_000 / freq.0 as u64
+    }
+}
+
+impl Add for Duration {
+    type Output = Duration;
+
+    fn add(self, other: Duration) -> Duration {
+        Duration(self.0 + other.0)
+    }
+}
+
+impl Sub for Duration {
+    type Output = Duration;
+
+    fn sub(self, other: Duration) -> Duration {
+        Duration(self.0 - other.0)
+    }
+}
@@ -0,0 +1,272 @@
+//! Interrupt driven receive buffering for the sercom UARTs.
+//!
+//! The receive complete interrupt moves each byte, along with any error the
+//! sercom flagged for it, into a static ring buffer. The application then
+//! reads from the ring with `embedded_hal::serial::Read` at its own pace.
+//!
+//! ```ignore
+//! static mut RING: RxRing<[u16; 256]> = RxRing::new([0; 256]);
+//!
+//! let (mut uart, mut rx) = BufferedUart::new(uart, unsafe { &mut RING });
+//!
+//! // In the SERCOM interrupt handler
+//! rx.service();
+//!
+//! // Anywhere else
+//! match uart.read() { ... }
+//! ```
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::marker::PhantomData;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::hal::blocking::serial::write::Default;
+use crate::hal::serial;
+use nb;
+
+const OVERRUN: u16 = 1 << 8;
+const FRAME: u16 = 1 << 9;
+const PARITY: u16 = 1 << 10;
+
+/// An error reported by `BufferedUart::read` in place of a byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+    /// Bytes were lost before the next byte, either in the sercom or because
+    /// the ring was full.
+    Overrun,
+    /// The byte had no valid stop bit and was discarded.
+    Frame,
+    /// The byte failed its parity check and was discarded.
+    Parity,
+}
+
+/// A byte taken from the sercom data register along with the status flags
+/// that applied to it.
+pub struct Received {
+    pub data: u8,
+    pub overrun: bool,
+    pub frame: bool,
+    pub parity: bool,
+}
+
+/// The UartRx trait lets a UART be drained from its interrupt handler. You
+/// should not implement this trait for yourself; only the implementations in
+/// the sercom module make sense.
+pub unsafe trait UartRx {
+    /// Enable the receive complete and error interrupts.
+    fn enable_rx_interrupts(&mut self);
+
+    /// Disable the receive complete and error interrupts.
+    fn disable_rx_interrupts(&mut self);
+
+    /// Take the next byte from the data register and clear its status flags.
+    ///
+    /// This is unsafe because it accesses the receive side of the sercom
+    /// without owning it, it must only be called from the one interrupt
+    /// handler that services this UART.
+    unsafe fn receive() -> Option<Received>;
+}
+
+/// Storage shared between the receive interrupt and the reader.
+///
+/// `S` is the backing store, an array of `u16` where the low byte is the
+/// received data and the high byte its error flags. One entry is always kept
+/// free so a ring of N entries holds N - 1 bytes, N must be at least 2.
+pub struct RxRing<S> {
+    storage: UnsafeCell<S>,
+    // The slots of `storage`, taken once by `attach` so that no reference to
+    // the storage exists while the producer and consumer use it
+    slots: *mut u16,
+    len: usize,
+    read: AtomicUsize,
+    write: AtomicUsize,
+}
+
+unsafe impl<S: Send> Sync for RxRing<S> {}
+
+impl<S> RxRing<S> {
+    pub const fn new(storage: S) -> Self {
+        Self {
+            storage: UnsafeCell::new(storage),
+            slots: core::ptr::null_mut(),
+            len: 0,
+            read: AtomicUsize::new(0),
+            write: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl<S: AsMut<[u16]>> RxRing<S> {
+    /// Empty the ring and take the raw slots of its storage. Panics if the
+    /// storage has fewer than 2 entries, a full ring could not be told apart
+    /// from an empty one.
+    fn attach(&mut self) {
+        let slice = self.storage.get_mut().as_mut();
+        assert!(slice.len() >= 2, "an RxRing needs at least 2 entries");
+
+        self.slots = slice.as_mut_ptr();
+        self.len = slice.len();
+        self.read.store(0, Ordering::Relaxed);
+        self.write.store(0, Ordering::Relaxed);
+    }
+}
+
+impl<S> RxRing<S> {
+    /// Push an entry, returns false if the ring is full. Only called by the
+    /// producer.
+    fn push(&self, entry: u16) -> bool {
+        let write = self.write.load(Ordering::Relaxed);
+        let next = if write + 1 == self.len { 0 } else { write + 1 };
+        if next == self.read.load(Ordering::Acquire) {
+            return false;
+        }
+
+        unsafe {
+            self.slots.add(write).write_volatile(entry);
+        }
+        self.write.store(next, Ordering::Release);
+
+        true
+    }
+
+    /// The oldest entry without removing it. Only called by the consumer.
+    fn peek(&self) -> Option<u16> {
+        let read = self.read.load(Ordering::Relaxed);
+        if read == self.write.load(Ordering::Acquire) {
+            return None;
+        }
+
+        Some(unsafe { self.slots.add(read).read_volatile() })
+    }
+
+    /// Replace the oldest entry. Only called by the consumer after `peek`.
+    fn replace(&self, entry: u16) {
+        let read = self.read.load(Ordering::Relaxed);
+        unsafe {
+            self.slots.add(read).write_volatile(entry);
+        }
+    }
+
+    /// Drop the oldest entry. Only called by the consumer after `peek`.
+    fn pop(&self) {
+        let read = self.read.load(Ordering::Relaxed);
+        let next = if read + 1 == self.len { 0 } else { read + 1 };
+        self.read.store(next, Ordering::Release);
+    }
+}
+
+/// The interrupt half of a `BufferedUart`, call `service` from the UART's
+/// sercom interrupt handler.
+pub struct RxInterrupt<U, S: 'static> {
+    ring: &'static RxRing<S>,
+    // Set when a byte could not be pushed, the next byte carries the overrun
+    lost: bool,
+    _uart: PhantomData<U>,
+}
+
+impl<U: UartRx, S: AsMut<[u16]>> RxInterrupt<U, S> {
+    /// Move every received byte into the ring.
+    pub fn service(&mut self) {
+        while let Some(received) = unsafe { U::receive() } {
+            let mut entry = received.data as u16;
+            if received.overrun || self.lost {
+                entry |= OVERRUN;
+            }
+            if received.frame {
+                entry |= FRAME;
+            }
+            if received.parity {
+                entry |= PARITY;
+            }
+
+            self.lost = !self.ring.push(entry);
+        }
+    }
+}
+
+/// A UART whose received bytes are buffered by its receive interrupt.
+/// Writes go straight to the UART.
+pub struct BufferedUart<U, S: 'static> {
+    uart: U,
+    ring: &'static RxRing<S>,
+}
+
+impl<U: UartRx, S: AsMut<[u16]>> BufferedUart<U, S> {
+    /// Start buffering `uart` into `ring`, enabling its receive interrupts.
+    /// The returned `RxInterrupt` must be serviced from the sercom interrupt
+    /// handler, which also needs unmasking in the NVIC.
+    ///
+    /// Panics if the ring has fewer than 2 entries.
+    pub fn new(mut uart: U, ring: &'static mut RxRing<S>) -> (Self, RxInterrupt<U, S>) {
+        ring.attach();
+        let ring: &'static RxRing<S> = ring;
+
+        uart.enable_rx_interrupts();
+
+        (
+            Self { uart, ring },
+            RxInterrupt {
+                ring,
+                lost: false,
+                _uart: PhantomData,
+            },
+        )
+    }
+
+    /// Stop buffering and release the UART, any buffered bytes are dropped.
+    pub fn free(mut self, _rx: RxInterrupt<U, S>) -> U {
+        self.uart.disable_rx_interrupts();
+        self.uart
+    }
+}
+
+impl<U, S: AsMut<[u16]>> serial::Read<u8> for BufferedUart<U, S> {
+    type Error = Error;
+
+    /// Errors are reported in order with the bytes. An overrun is reported
+    /// before the byte that followed the lost bytes, that byte is returned by
+    /// the next read. Frame and parity errors replace their byte.
+    fn read(&mut self) -> nb::Result<u8, Error> {
+        let entry = match self.ring.peek() {
+            Some(entry) => entry,
+            None => return Err(nb::Error::WouldBlock),
+        };
+
+        if entry & OVERRUN != 0 {
+            self.ring.replace(entry & !OVERRUN);
+            return Err(nb::Error::Other(Error::Overrun));
+        }
+
+        self.ring.pop();
+
+        if entry & FRAME != 0 {
+            Err(nb::Error::Other(Error::Frame))
+        } else if entry & PARITY != 0 {
+            Err(nb::Error::Other(Error::Parity))
+        } else {
+            Ok(entry as u8)
+        }
+    }
+}
+
+impl<U: serial::Write<u8>, S> serial::Write<u8> for BufferedUart<U, S> {
+    type Error = U::Error;
+
+    fn write(&mut self, word: u8) -> nb::Result<(), Self::Error> {
+        self.uart.write(word)
+    }
+
+    fn flush(&mut self) -> nb::Result<(), Self::Error> {
+        self.uart.flush()
+    }
+}
+
+impl<U: serial::Write<u8>, S> Default<u8> for BufferedUart<U, S> {}
+
+impl<U: serial::Write<u8>, S> fmt::Write for BufferedUart<U, S> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        use crate::hal::blocking::serial::Write;
+
+        self.bwrite_all(s.as_bytes()).map_err(|_| fmt::Error)
+    }
+}
@@ -303,12 +303,15 @@ impl GenericClockController {
 clock_generator!(
     (tcc0, Tcc0Clock, TCC0),
     (tc1_tc2, Tc1Tc2Clock, TC1_TC2),
+    (eic, EicClock, EIC),
     (sercom0_core, Sercom0CoreClock, SERCOM0_CORE),
     (sercom1_core, Sercom1CoreClock, SERCOM1_CORE),
     (sercom2_core, Sercom2CoreClock, SERCOM2_CORE),
     (usb, UsbClock, USB),
     (rtc, RtcClock, RTC),
     (adc, AdcClock, ADC),
+    (ac_dig, AcDigClock, AC_DIG),
+    (ac_ana, AcAnaClock, AC_ANA),
 );
 
 /// The frequency of the 48Mhz source.
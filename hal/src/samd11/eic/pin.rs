@@ -0,0 +1,220 @@
+use crate::{
+    target_device,
+    gpio::{
+        self,
+        Port,
+        IntoFunction,
+    },
+};
+
+/// The EicPin trait makes it more ergonomic to convert a gpio pin into an EIC
+/// pin. You should not implement this trait for yourself; only the
+/// implementations in the EIC module make sense.
+pub trait EicPin<T> {
+    fn into_ei(self, port: &mut Port) -> T;
+}
+
+pub type Sense = target_device::eic::config::SENSE0_A;
+
+pub type NmiSense = target_device::eic::nmictrl::NMISENSE_A;
+
+/// The non-maskable interrupt pin. Its interrupt is taken by the
+/// `NonMaskableInt` exception handler, which runs even while interrupts are
+/// masked.
+pub struct NmiPin<GPIO>(GPIO);
+
+impl<GPIO> NmiPin<GPIO> {
+    /// Construct the NMI from the appropriate pin in any mode.
+    /// You may find it more convenient to use the `into_ei` trait
+    /// and avoid referencing the pin type.
+    pub fn new(pin: GPIO) -> Self {
+        NmiPin(pin)
+    }
+
+    pub fn sense(&mut self, eic: &mut super::EIC, sense: NmiSense) {
+        eic.eic.nmictrl.modify(|_, w| w.nmisense().variant(sense));
+    }
+
+    pub fn filter(&mut self, eic: &mut super::EIC, filter: bool) {
+        eic.eic.nmictrl.modify(|_, w| w.nmifilten().bit(filter));
+    }
+
+    pub fn is_interrupt(&mut self) -> bool {
+        nmi_is_interrupt()
+    }
+
+    pub fn clear_interrupt(&mut self) {
+        nmi_clear_interrupt()
+    }
+}
+
+/// Whether the NMI pin has detected its sense condition. The `NmiPin` can't
+/// be shared safely with the `NonMaskableInt` handler, so this is available
+/// without it.
+pub fn nmi_is_interrupt() -> bool {
+    unsafe { &(*target_device::EIC::ptr()) }.nmiflag.read().nmi().bit_is_set()
+}
+
+/// Clear the NMI flag, call this from the `NonMaskableInt` handler.
+pub fn nmi_clear_interrupt() {
+    unsafe { &(*target_device::EIC::ptr()) }.nmiflag.write(|w| w.nmi().set_bit());
+}
+
+impl<MODE> EicPin<NmiPin<gpio::Pa8<gpio::PfA>>> for gpio::Pa8<MODE> {
+    fn into_ei(self, port: &mut Port) -> NmiPin<gpio::Pa8<gpio::PfA>> {
+        NmiPin::new(self.into_function(port))
+    }
+}
+
+/// The pad macro defines the given EIC pin and implements EicPin for the
+/// given pins. The EicPin implementation will configure the pin for the
+/// appropriate function and return the pin wrapped in the EIC type.
+macro_rules! ei {
+    ($PadType:ident [ $num:expr ] {
+        $($(#[$attr:meta])* $PinType:ident ,)+
+    }
+    ) => {
+crate::paste::item! {
+    /// Represents a numbered external interrupt. The external interrupt is
+    /// generic over any pin, only the EicPin implementations in this module
+    /// make sense.
+    pub struct [<$PadType $num>]<GPIO>(GPIO);
+
+    //impl !Send for [<$PadType $num>]<GPIO> {};
+    //impl !Sync for [<$PadType $num>]<GPIO> {}}
+
+    impl<GPIO> [<$PadType $num>]<GPIO> {
+        /// Construct pad from the appropriate pin in any mode.
+        /// You may find it more convenient to use the `into_pad` trait
+        /// and avoid referencing the pad type.
+        pub fn new(pin: GPIO) -> Self {
+            [<$PadType $num>](pin)
+        }
+
+        /// Configure the eic with options for this external interrupt
+        pub fn enable_event(&mut self, eic: &mut super::EIC) {
+            eic.eic.evctrl.modify(|_, w| {
+                w.[<extinteo $num>]().set_bit()
+            });
+        }
+
+        pub fn enable_interrupt(&mut self, eic: &mut super::EIC) {
+            eic.eic.intenset.write(|w| {
+                w.[<extint $num>]().set_bit()
+            });
+        }
+
+        pub fn enable_interrupt_wake(&mut self, eic: &mut super::EIC) {
+            eic.eic.wakeup.modify(|_, w| {
+                w.[<wakeupen $num>]().set_bit()
+            })
+        }
+
+        pub fn disable_interrupt(&mut self, eic: &mut super::EIC) {
+            eic.eic.intenclr.write(|w| {
+                w.[<extint $num>]().set_bit()
+            });
+        }
+
+        pub fn is_interrupt(&mut self) -> bool {
+            unsafe { &(*target_device::EIC::ptr()) }.intflag.read().[<extint $num>]().bit_is_set()
+        }
+
+        pub fn clear_interrupt(&mut self) {
+            unsafe { &(*target_device::EIC::ptr()) }.intflag.write(|w| {
+                w.[<extint $num>]().set_bit()
+            });
+        }
+
+        pub fn sense(&mut self, _eic: &mut super::EIC, sense: Sense) {
+            // There is only one config block for the eight external interrupts
+            let config = unsafe { &(*target_device::EIC::ptr()).config[0] };
+
+            config.modify(|_, w| unsafe {
+                // Which of the eight eic configs
+                match $num & 0b111 {
+                    0b000 => w.sense0().bits(sense as u8),
+                    0b001 => w.sense1().bits(sense as u8),
+                    0b010 => w.sense2().bits(sense as u8),
+                    0b011 => w.sense3().bits(sense as u8),
+                    0b100 => w.sense4().bits(sense as u8),
+                    0b101 => w.sense5().bits(sense as u8),
+                    0b110 => w.sense6().bits(sense as u8),
+                    0b111 => w.sense7().bits(sense as u8),
+                    _ => unimplemented!(),
+                }
+            });
+        }
+
+        pub fn filter(&mut self, _eic: &mut super::EIC, filter: bool) {
+            // There is only one config block for the eight external interrupts
+            let config = unsafe { &(*target_device::EIC::ptr()).config[0] };
+
+            config.modify(|_, w| {
+                // Which of the eight eic configs
+                match $num & 0b111 {
+                    0b000 => w.filten0().bit(filter),
+                    0b001 => w.filten1().bit(filter),
+                    0b010 => w.filten2().bit(filter),
+                    0b011 => w.filten3().bit(filter),
+                    0b100 => w.filten4().bit(filter),
+                    0b101 => w.filten5().bit(filter),
+                    0b110 => w.filten6().bit(filter),
+                    0b111 => w.filten7().bit(filter),
+                    _ => unimplemented!(),
+                }
+            });
+        }
+    }
+
+    $(
+        $(
+            #[$attr]
+        )*
+        impl<MODE> EicPin<[<$PadType $num>]<gpio::$PinType<gpio::PfA>>> for gpio::$PinType<MODE> {
+            fn into_ei(self, port: &mut Port) -> [<$PadType $num>]<gpio::$PinType<gpio::PfA>> {
+                [<$PadType $num>]::new(self.into_function(port))
+            }
+        }
+    )+
+}
+
+    };
+}
+
+ei!(ExtInt[0] {
+    Pa14,
+});
+
+ei!(ExtInt[1] {
+    Pa15,
+});
+
+ei!(ExtInt[2] {
+    Pa2,
+    Pa30,
+});
+
+ei!(ExtInt[3] {
+    Pa31,
+});
+
+ei!(ExtInt[4] {
+    Pa4,
+    Pa24,
+});
+
+ei!(ExtInt[5] {
+    Pa5,
+    Pa25,
+});
+
+ei!(ExtInt[6] {
+    Pa8,
+    Pa22,
+});
+
+ei!(ExtInt[7] {
+    Pa9,
+    Pa23,
+});
@@ -2,6 +2,8 @@ pub mod calibration;
 pub mod clock;
 pub mod sercom;
 pub mod timer;
+pub mod eic;
+pub mod rtc;
 
 #[cfg(feature = "unproven")]
 pub mod pwm;
@@ -15,6 +17,8 @@ pub use serial_number::*;
 #[cfg(feature = "unproven")]
 pub mod adc;
 
+pub mod ac;
+
 /// ResetCause represents the reason the MCU was reset.
 #[derive(Debug, Clone, Copy)]
 pub enum ResetCause {
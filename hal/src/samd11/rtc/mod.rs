@@ -0,0 +1,403 @@
+use core::marker::PhantomData;
+
+use crate::{
+	target_device,
+	clock,
+	time::*,
+};
+
+pub mod monotonic;
+pub use monotonic::Monotonic;
+
+pub type Prescaler = target_device::rtc::mode0::ctrl::PRESCALER_A;
+
+/// Which fields of the clock must equal an alarm for it to match, from
+/// seconds only up to the full date and time.
+pub type AlarmMatch = target_device::rtc::mode2::mask::SEL_A;
+
+/// The RTC counts a single 32 bit value that can be cleared when it matches
+/// the compare value.
+pub struct Count32Mode;
+
+/// The RTC counts a 16 bit value that wraps at the period value, with two
+/// compare values.
+pub struct Count16Mode;
+
+/// The RTC keeps a calendar date and time with one second resolution.
+pub struct ClockMode;
+
+/// A calendar date and 24 hour time for `ClockMode`. The RTC stores the year
+/// as an offset from 2000, so years from 2000 to 2063 can be represented.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DateTime {
+	pub year: u16,
+	pub month: u8,
+	pub day: u8,
+	pub hours: u8,
+	pub minutes: u8,
+	pub seconds: u8,
+}
+
+const BASE_YEAR: u16 = 2000;
+
+// Interrupt flag bits shared by the three modes
+const INT_CMP0: u8 = 1 << 0;
+const INT_OVF: u8 = 1 << 7;
+
+pub struct RTC<Mode = Count32Mode> {
+	rtc: target_device::RTC,
+	_mode: PhantomData<Mode>,
+}
+
+impl RTC<Count32Mode> {
+	pub fn five_minutes(pm: &mut target_device::PM, clock: clock::RtcClock, rtc: target_device::RTC) -> Self {
+		Self::compare_at_32khz(pm, clock, rtc, 9_600)
+	}
+
+	pub fn ten_seconds(pm: &mut target_device::PM, clock: clock::RtcClock, rtc: target_device::RTC) -> Self {
+		Self::compare_at_32khz(pm, clock, rtc, 312)
+	}
+
+	fn compare_at_32khz(pm: &mut target_device::PM, clock: clock::RtcClock, rtc: target_device::RTC, count: u32) -> Self {
+		let hz = clock.freq();
+		assert!(hz == 32_000.hz());
+
+		let mut rtc = Self::count32(pm, clock, rtc, Prescaler::DIV1024, true);
+		rtc.set_compare(count);
+		rtc.enable_compare_interrupt();
+		rtc
+	}
+
+	/// Count up from zero at the clock frequency divided by `prescaler`.
+	/// When `clear_on_match` is set the count returns to zero when it
+	/// matches the compare value, giving a periodic compare interrupt.
+	pub fn count32(pm: &mut target_device::PM, clock: clock::RtcClock, rtc: target_device::RTC, prescaler: Prescaler, clear_on_match: bool) -> Self {
+		let rtc = reset(pm, clock, rtc);
+
+		rtc.mode0().ctrl.write(|w| {
+			w.matchclr().bit(clear_on_match);
+			w.mode().count32();
+			w.prescaler().variant(prescaler)
+		});
+
+		RTC::enable(rtc)
+	}
+
+	pub fn count(&self) -> u32 {
+		self.read_request();
+		self.rtc.mode0().count.read().bits()
+	}
+
+	pub fn set_count(&mut self, count: u32) {
+		self.rtc.mode0().count.write(|w| unsafe {
+			w.bits(count)
+		});
+		self.sync();
+	}
+
+	pub fn set_compare(&mut self, compare: u32) {
+		self.rtc.mode0().comp[0].write(|w| unsafe {
+			w.bits(compare)
+		});
+		self.sync();
+	}
+
+	pub fn enable_compare_interrupt(&mut self) {
+		self.enable_interrupts(INT_CMP0);
+	}
+
+	pub fn disable_compare_interrupt(&mut self) {
+		self.disable_interrupts(INT_CMP0);
+	}
+
+	pub fn is_compare(&self) -> bool {
+		self.is_interrupt(INT_CMP0)
+	}
+
+	pub fn clear_compare(&mut self) {
+		self.clear_interrupts(INT_CMP0);
+	}
+
+	pub fn clear_interrupt(&mut self) {
+		self.clear_compare();
+	}
+}
+
+impl RTC<Count16Mode> {
+	/// Count up from zero at the clock frequency divided by `prescaler`,
+	/// overflowing back to zero after `period`.
+	pub fn count16(pm: &mut target_device::PM, clock: clock::RtcClock, rtc: target_device::RTC, prescaler: Prescaler, period: u16) -> Self {
+		let rtc = reset(pm, clock, rtc);
+
+		rtc.mode1().ctrl.write(|w| unsafe {
+			w.mode().count16();
+			w.prescaler().bits(u8::from(prescaler))
+		});
+
+		let mut rtc = RTC::enable(rtc);
+		rtc.set_period(period);
+		rtc
+	}
+
+	pub fn count(&self) -> u16 {
+		self.read_request();
+		self.rtc.mode1().count.read().bits()
+	}
+
+	pub fn set_count(&mut self, count: u16) {
+		self.rtc.mode1().count.write(|w| unsafe {
+			w.bits(count)
+		});
+		self.sync();
+	}
+
+	pub fn set_period(&mut self, period: u16) {
+		self.rtc.mode1().per.write(|w| unsafe {
+			w.bits(period)
+		});
+		self.sync();
+	}
+
+	/// Set one of the two compare values. Panics if `index` is out of range.
+	pub fn set_compare(&mut self, index: usize, compare: u16) {
+		self.rtc.mode1().comp[index].write(|w| unsafe {
+			w.bits(compare)
+		});
+		self.sync();
+	}
+
+	pub fn enable_compare_interrupt(&mut self, index: usize) {
+		self.enable_interrupts(compare_flag(index));
+	}
+
+	pub fn disable_compare_interrupt(&mut self, index: usize) {
+		self.disable_interrupts(compare_flag(index));
+	}
+
+	pub fn is_compare(&self, index: usize) -> bool {
+		self.is_interrupt(compare_flag(index))
+	}
+
+	pub fn clear_compare(&mut self, index: usize) {
+		self.clear_interrupts(compare_flag(index));
+	}
+}
+
+impl RTC<ClockMode> {
+	/// Keep the date and time. The clock is divided down to 1Hz, so it must
+	/// run at a power of two multiple of 1Hz, up to 1024Hz; a 1kHz generic
+	/// clock is close enough.
+	pub fn clock_mode(pm: &mut target_device::PM, clock: clock::RtcClock, rtc: target_device::RTC) -> Self {
+		let hz = clock.freq();
+		let prescaler = one_hertz_prescaler(hz);
+
+		let rtc = reset(pm, clock, rtc);
+
+		rtc.mode2().ctrl.write(|w| unsafe {
+			w.mode().clock();
+			w.clkrep().clear_bit();
+			w.prescaler().bits(u8::from(prescaler))
+		});
+
+		RTC::enable(rtc)
+	}
+
+	pub fn date_time(&self) -> DateTime {
+		self.read_request();
+		let clock = self.rtc.mode2().clock.read();
+
+		DateTime {
+			year: BASE_YEAR + clock.year().bits() as u16,
+			month: clock.month().bits(),
+			day: clock.day().bits(),
+			hours: clock.hour().bits(),
+			minutes: clock.minute().bits(),
+			seconds: clock.second().bits(),
+		}
+	}
+
+	pub fn set_date_time(&mut self, date_time: DateTime) {
+		let year = year_offset(&date_time);
+		self.rtc.mode2().clock.write(|w| unsafe {
+			w.year().bits(year);
+			w.month().bits(date_time.month);
+			w.day().bits(date_time.day);
+			w.hour().bits(date_time.hours);
+			w.minute().bits(date_time.minutes);
+			w.second().bits(date_time.seconds)
+		});
+		self.sync();
+	}
+
+	/// Match the alarm against the fields of `date_time` selected by
+	/// `matching`, the others are ignored.
+	pub fn set_alarm(&mut self, date_time: DateTime, matching: AlarmMatch) {
+		let year = year_offset(&date_time);
+		let mode = self.rtc.mode2();
+
+		mode.alarm0.write(|w| unsafe {
+			w.year().bits(year);
+			w.month().bits(date_time.month);
+			w.day().bits(date_time.day);
+			w.hour().bits(date_time.hours);
+			w.minute().bits(date_time.minutes);
+			w.second().bits(date_time.seconds)
+		});
+		self.sync();
+
+		mode.mask0.write(|w| w.sel().variant(matching));
+		self.sync();
+	}
+
+	pub fn enable_alarm_interrupt(&mut self) {
+		self.enable_interrupts(INT_CMP0);
+	}
+
+	pub fn disable_alarm_interrupt(&mut self) {
+		self.disable_interrupts(INT_CMP0);
+	}
+
+	pub fn is_alarm(&self) -> bool {
+		self.is_interrupt(INT_CMP0)
+	}
+
+	pub fn clear_alarm(&mut self) {
+		self.clear_interrupts(INT_CMP0);
+	}
+}
+
+impl<Mode> RTC<Mode> {
+	fn enable(rtc: target_device::RTC) -> Self {
+		while rtc.mode0().status.read().syncbusy().bit_is_set() {
+			cortex_m::asm::nop();
+		}
+
+		rtc.mode0().ctrl.modify(|_, w| {
+			w.enable().set_bit()
+		});
+
+		let rtc = RTC {
+			rtc,
+			_mode: PhantomData,
+		};
+		rtc.sync();
+		rtc
+	}
+
+	pub fn enable_overflow_interrupt(&mut self) {
+		self.enable_interrupts(INT_OVF);
+	}
+
+	pub fn disable_overflow_interrupt(&mut self) {
+		self.disable_interrupts(INT_OVF);
+	}
+
+	pub fn is_overflow(&self) -> bool {
+		self.is_interrupt(INT_OVF)
+	}
+
+	pub fn clear_overflow(&mut self) {
+		self.clear_interrupts(INT_OVF);
+	}
+
+	/// Disable the RTC and release it, it can then be set up in another mode.
+	pub fn free(self) -> target_device::RTC {
+		self.rtc.mode0().ctrl.modify(|_, w| {
+			w.enable().clear_bit()
+		});
+		self.sync();
+
+		self.rtc
+	}
+
+	fn enable_interrupts(&mut self, flags: u8) {
+		self.rtc.mode0().intenset.write(|w| unsafe {
+			w.bits(flags)
+		});
+	}
+
+	fn disable_interrupts(&mut self, flags: u8) {
+		self.rtc.mode0().intenclr.write(|w| unsafe {
+			w.bits(flags)
+		});
+	}
+
+	fn is_interrupt(&self, flags: u8) -> bool {
+		self.rtc.mode0().intflag.read().bits() & flags != 0
+	}
+
+	fn clear_interrupts(&mut self, flags: u8) {
+		self.rtc.mode0().intflag.write(|w| unsafe {
+			w.bits(flags)
+		});
+	}
+
+	/// Synchronise the count or clock register so it can be read
+	fn read_request(&self) {
+		self.rtc.mode0().readreq.write(|w| {
+			w.rreq().set_bit()
+		});
+		self.sync();
+	}
+
+	fn sync(&self) {
+		while self.rtc.mode0().status.read().syncbusy().bit_is_set() {
+			cortex_m::asm::nop();
+		}
+	}
+}
+
+/// Enable the RTC bus clock, then disable and reset it ready to be set up.
+fn reset(pm: &mut target_device::PM, _clock: clock::RtcClock, rtc: target_device::RTC) -> target_device::RTC {
+	pm.apbamask.modify(|_, w| w.rtc_().set_bit());
+
+	let mode = rtc.mode0();
+
+	// Disable and reset rtc
+	mode.ctrl.modify(|_, w| {
+		w.enable().clear_bit()
+	});
+	while mode.status.read().syncbusy().bit_is_set() {
+		cortex_m::asm::nop();
+	}
+
+	mode.ctrl.modify(|_, w| {
+		w.swrst().set_bit()
+	});
+	while mode.status.read().syncbusy().bit_is_set() {
+		cortex_m::asm::nop();
+	}
+
+	rtc
+}
+
+fn compare_flag(index: usize) -> u8 {
+	assert!(index < 2, "the rtc has two 16 bit compare values");
+	INT_CMP0 << index
+}
+
+/// The prescaler that divides `hz` down to the nearest power of two to 1Hz.
+fn one_hertz_prescaler(hz: Hertz) -> Prescaler {
+	let upper = hz.0.next_power_of_two();
+	let divider = if upper - hz.0 > hz.0 - upper / 2 { upper / 2 } else { upper };
+
+	match divider {
+		1 => Prescaler::DIV1,
+		2 => Prescaler::DIV2,
+		4 => Prescaler::DIV4,
+		8 => Prescaler::DIV8,
+		16 => Prescaler::DIV16,
+		32 => Prescaler::DIV32,
+		64 => Prescaler::DIV64,
+		128 => Prescaler::DIV128,
+		256 => Prescaler::DIV256,
+		512 => Prescaler::DIV512,
+		1024 => Prescaler::DIV1024,
+		_ => panic!("the rtc clock is too fast for clock mode"),
+	}
+}
+
+fn year_offset(date_time: &DateTime) -> u8 {
+	assert!(date_time.year >= BASE_YEAR && date_time.year < BASE_YEAR + 64, "the rtc can only represent the years 2000 to 2063");
+	(date_time.year - BASE_YEAR) as u8
+}
@@ -1,7 +1,7 @@
 //! Working with timer counter hardware
-use crate::target_device::tc1::COUNT16;
+use crate::target_device::tc1::{COUNT16, COUNT32, COUNT8};
 #[allow(unused)]
-use crate::target_device::{PM, TC1};
+use crate::target_device::{PM, TC1, TC2};
 use hal::timer::{CountDown, Periodic};
 
 use crate::clock;
@@ -9,12 +9,13 @@ use crate::time::Hertz;
 use nb;
 use void::Void;
 
+// Note:
+// TC1 + TC2 can be paired to make a 32-bit counter
+
 /// A generic hardware timer counter.
-/// The counters are exposed in 16-bit mode only.
-/// The hardware allows configuring the 8-bit mode
-/// and pairing up some instances to run in 32-bit
-/// mode, but that functionality is not currently
-/// exposed by this hal implementation.
+/// The counter runs in 16-bit mode, see `TimerCounter8`
+/// for 8-bit mode and `TimerCounter32` for pairs of
+/// counters in 32-bit mode.
 /// TimerCounter implements both the `Periodic` and
 /// the `CountDown` embedded_hal timer traits.
 /// Before a hardware timer can be used, it must first
@@ -31,6 +32,24 @@ pub trait Count16 {
     fn count_16(&self) -> &COUNT16;
 }
 
+/// Set the prescaler of a CTRLA writer to one of the dividers that
+/// `TimerParams` picks
+macro_rules! prescaler {
+    ($w:ident, $divider:expr) => {
+        match $divider {
+            1 => $w.prescaler().div1(),
+            2 => $w.prescaler().div2(),
+            4 => $w.prescaler().div4(),
+            8 => $w.prescaler().div8(),
+            16 => $w.prescaler().div16(),
+            64 => $w.prescaler().div64(),
+            256 => $w.prescaler().div256(),
+            1024 => $w.prescaler().div1024(),
+            _ => unreachable!(),
+        };
+    };
+}
+
 impl<TC> Periodic for TimerCounter<TC> {}
 impl<TC> CountDown for TimerCounter<TC>
 where
@@ -71,17 +90,7 @@ where
         count.cc[0].write(|w| unsafe { w.cc().bits(cycles as u16) });
 
         count.ctrla.modify(|_, w| {
-            match divider {
-                1 => w.prescaler().div1(),
-                2 => w.prescaler().div2(),
-                4 => w.prescaler().div4(),
-                8 => w.prescaler().div8(),
-                16 => w.prescaler().div16(),
-                64 => w.prescaler().div64(),
-                256 => w.prescaler().div256(),
-                1024 => w.prescaler().div1024(),
-                _ => unreachable!(),
-            };
+            prescaler!(w, divider);
             // Enable Match Frequency Waveform generation
             w.wavegen().mfrq();
             w.enable().set_bit()
@@ -121,6 +130,220 @@ where
     }
 }
 
+/// A hardware timer counter in 8-bit mode, counting up to its period
+/// register. The short range suits high frequencies at a low prescaler.
+pub struct TimerCounter8<TC> {
+    freq: Hertz,
+    tc: TC,
+}
+
+/// Two hardware timer counters paired in 32-bit mode. The master counter's
+/// registers control the pair, the slave is held so that it can't be used
+/// on its own while paired.
+pub struct TimerCounter32<Master, Slave> {
+    freq: Hertz,
+    tc: Master,
+    slave: Slave,
+}
+
+/// This is a helper trait to make it easier to make most of the
+/// TimerCounter8 impl generic.  It doesn't make too much sense to
+/// to try to implement this trait outside of this module.
+pub trait Count8 {
+    fn count_8(&self) -> &COUNT8;
+}
+
+/// This is a helper trait to make it easier to make most of the
+/// TimerCounter32 impl generic. It is only implemented for the counters
+/// that can be the master of a pair.
+pub trait Count32 {
+    fn count_32(&self) -> &COUNT32;
+}
+
+impl<TC> Periodic for TimerCounter8<TC> {}
+impl<TC> CountDown for TimerCounter8<TC>
+where
+    TC: Count8,
+{
+    type Time = Hertz;
+
+    fn start<T>(&mut self, timeout: T)
+    where
+        T: Into<Hertz>,
+    {
+        let params = TimerParams::new_for_counter(timeout, self.freq.0, u8::max_value() as u32);
+        let divider = params.divider;
+        let cycles = params.cycles;
+
+        let count = self.tc.count_8();
+
+        // Disable the timer while we reconfigure it
+        count.ctrla.modify(|_, w| w.enable().clear_bit());
+        while count.status.read().syncbusy().bit_is_set() {}
+
+        count.ctrla.write(|w| w.swrst().set_bit());
+        while count.status.read().syncbusy().bit_is_set() {}
+        // the SVD erroneously marks swrst as write-only, so we
+        // need to manually read the bit here
+        while count.ctrla.read().bits() & 1 != 0 {}
+
+        // The mode sets the layout of the other registers, so it goes
+        // before the period
+        count.ctrla.modify(|_, w| w.mode().count8());
+
+        count.ctrlbset.write(|w| {
+            // Count up when the direction bit is zero
+            w.dir().clear_bit();
+            // Periodic
+            w.oneshot().clear_bit()
+        });
+
+        // Set TOP value for nfrq mode
+        count.per.write(|w| unsafe { w.per().bits(cycles as u8) });
+        while count.status.read().syncbusy().bit_is_set() {}
+
+        count.ctrla.modify(|_, w| {
+            prescaler!(w, divider);
+            // Enable Normal Frequency Waveform generation, which counts up to PER
+            w.wavegen().nfrq();
+            w.enable().set_bit()
+        });
+    }
+
+    fn wait(&mut self) -> nb::Result<(), Void> {
+        let count = self.tc.count_8();
+        if count.intflag.read().ovf().bit_is_set() {
+            // Writing a 1 clears the flag
+            count.intflag.write(|w| w.ovf().set_bit());
+            Ok(())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+}
+
+impl<TC> TimerCounter8<TC>
+where
+    TC: Count8,
+{
+    /// Enable the interrupt generation for this hardware timer.
+    /// This method only sets the clock configuration to trigger
+    /// the interrupt; it does not configure the interrupt controller
+    /// or define an interrupt handler.
+    pub fn enable_interrupt(&mut self) {
+        self.tc.count_8().intenset.write(|w| w.ovf().set_bit());
+    }
+
+    /// Disables interrupt generation for this hardware timer.
+    /// This method only sets the clock configuration to prevent
+    /// triggering the interrupt; it does not configure the interrupt
+    /// controller.
+    pub fn disable_interrupt(&mut self) {
+        self.tc.count_8().intenclr.write(|w| w.ovf().set_bit());
+    }
+
+    pub fn clear_interrupt(&mut self) {
+        self.tc.count_8().intflag.write(|w| w.ovf().set_bit());
+    }
+}
+
+impl<Master, Slave> Periodic for TimerCounter32<Master, Slave> {}
+impl<Master, Slave> CountDown for TimerCounter32<Master, Slave>
+where
+    Master: Count32,
+{
+    type Time = Hertz;
+
+    fn start<T>(&mut self, timeout: T)
+    where
+        T: Into<Hertz>,
+    {
+        let params = TimerParams::new_for_counter(timeout, self.freq.0, u32::max_value());
+        let divider = params.divider;
+        let cycles = params.cycles;
+
+        let count = self.tc.count_32();
+
+        // Disable the timer while we reconfigure it
+        count.ctrla.modify(|_, w| w.enable().clear_bit());
+        while count.status.read().syncbusy().bit_is_set() {}
+
+        count.ctrla.write(|w| w.swrst().set_bit());
+        while count.status.read().syncbusy().bit_is_set() {}
+        // the SVD erroneously marks swrst as write-only, so we
+        // need to manually read the bit here
+        while count.ctrla.read().bits() & 1 != 0 {}
+
+        // The mode sets the layout of the other registers, so it goes
+        // before the period. The slave follows the master in this mode.
+        count.ctrla.modify(|_, w| w.mode().count32());
+
+        count.ctrlbset.write(|w| {
+            // Count up when the direction bit is zero
+            w.dir().clear_bit();
+            // Periodic
+            w.oneshot().clear_bit()
+        });
+
+        // Set TOP value for mfrq mode
+        count.cc[0].write(|w| unsafe { w.cc().bits(cycles) });
+        while count.status.read().syncbusy().bit_is_set() {}
+
+        count.ctrla.modify(|_, w| {
+            prescaler!(w, divider);
+            // Enable Match Frequency Waveform generation
+            w.wavegen().mfrq();
+            w.enable().set_bit()
+        });
+    }
+
+    fn wait(&mut self) -> nb::Result<(), Void> {
+        let count = self.tc.count_32();
+        if count.intflag.read().ovf().bit_is_set() {
+            // Writing a 1 clears the flag
+            count.intflag.write(|w| w.ovf().set_bit());
+            Ok(())
+        } else {
+            Err(nb::Error::WouldBlock)
+        }
+    }
+}
+
+impl<Master, Slave> TimerCounter32<Master, Slave>
+where
+    Master: Count32,
+{
+    /// Enable the interrupt generation for this hardware timer.
+    /// The interrupt is raised by the master counter.
+    /// This method only sets the clock configuration to trigger
+    /// the interrupt; it does not configure the interrupt controller
+    /// or define an interrupt handler.
+    pub fn enable_interrupt(&mut self) {
+        self.tc.count_32().intenset.write(|w| w.ovf().set_bit());
+    }
+
+    /// Disables interrupt generation for this hardware timer.
+    /// This method only sets the clock configuration to prevent
+    /// triggering the interrupt; it does not configure the interrupt
+    /// controller.
+    pub fn disable_interrupt(&mut self) {
+        self.tc.count_32().intenclr.write(|w| w.ovf().set_bit());
+    }
+
+    pub fn clear_interrupt(&mut self) {
+        self.tc.count_32().intflag.write(|w| w.ovf().set_bit());
+    }
+
+    /// Disable the pair and release the two counters.
+    pub fn free(self) -> (Master, Slave) {
+        let count = self.tc.count_32();
+        count.ctrla.modify(|_, w| w.enable().clear_bit());
+        while count.status.read().syncbusy().bit_is_set() {}
+
+        (self.tc, self.slave)
+    }
+}
+
 macro_rules! tc {
     ($($TYPE:ident: ($TC:ident, $pm:ident, $clock:ident),)+) => {
         $(
@@ -132,6 +355,12 @@ impl Count16 for $TC {
     }
 }
 
+impl Count8 for $TC {
+    fn count_8(&self) -> &COUNT8 {
+        self.count8()
+    }
+}
+
 impl TimerCounter<$TC>
 {
     /// Configure this timer counter instance.
@@ -156,6 +385,70 @@ impl TimerCounter<$TC>
         }
     }
 }
+
+impl TimerCounter8<$TC>
+{
+    /// Configure this timer counter instance in 8-bit mode.
+    /// The clock is obtained from the `GenericClockController` instance
+    /// and its frequency impacts the resolution and maximum range of
+    /// the timeout values that can be passed to the `start` method.
+    pub fn $pm(clock: &clock::$clock, tc: $TC, pm: &mut PM) -> Self {
+        pm.apbcmask.modify(|_, w| w.$pm().set_bit());
+        {
+            let count = tc.count_8();
+
+            // Disable the timer while we reconfigure it
+            count.ctrla.modify(|_, w| w.enable().clear_bit());
+            while count.status.read().syncbusy().bit_is_set() {}
+        }
+        Self {
+            freq: clock.freq(),
+            tc,
+        }
+    }
+}
+        )+
+    }
+}
+
+macro_rules! tc32 {
+    ($($TYPE:ident: ($MASTER:ident, $SLAVE:ident, $pm:ident, $master_pm:ident, $slave_pm:ident, $clock:ident),)+) => {
+        $(
+pub type $TYPE = TimerCounter32<$MASTER, $SLAVE>;
+
+impl Count32 for $MASTER {
+    fn count_32(&self) -> &COUNT32 {
+        self.count32()
+    }
+}
+
+impl TimerCounter32<$MASTER, $SLAVE>
+{
+    /// Configure this pair of timer counter instances in 32-bit mode.
+    /// Both counters share the clock obtained from the
+    /// `GenericClockController`, its frequency impacts the resolution
+    /// and maximum range of the timeout values that can be passed to the
+    /// `start` method.
+    pub fn $pm(clock: &clock::$clock, master: $MASTER, slave: $SLAVE, pm: &mut PM) -> Self {
+        // Both counters of the pair need their bus clock
+        pm.apbcmask.modify(|_, w| {
+            w.$master_pm().set_bit();
+            w.$slave_pm().set_bit()
+        });
+        {
+            let count = master.count_32();
+
+            // Disable the timer while we reconfigure it
+            count.ctrla.modify(|_, w| w.enable().clear_bit());
+            while count.status.read().syncbusy().bit_is_set() {}
+        }
+        Self {
+            freq: clock.freq(),
+            tc: master,
+            slave,
+        }
+    }
+}
         )+
     }
 }
@@ -169,12 +462,21 @@ pub struct TimerParams {
 
 impl TimerParams {
     pub fn new<T>(timeout: T, src_freq: u32) -> Self
+    where
+        T: Into<Hertz>,
+    {
+        Self::new_for_counter(timeout, src_freq, u16::max_value() as u32)
+    }
+
+    /// Compute the divider and cycles for a counter that counts up to `max`,
+    /// which is the largest value of its 8, 16 or 32 bit counter.
+    pub fn new_for_counter<T>(timeout: T, src_freq: u32, max: u32) -> Self
     where
         T: Into<Hertz>,
     {
         let timeout = timeout.into();
-        let ticks: u32 = src_freq / timeout.0.max(1);
-        let divider = ((ticks >> 16) + 1).next_power_of_two();
+        let ticks: u32 = src_freq/timeout.0.max(1);
+        let divider = ((ticks as u64 / (max as u64 + 1)) as u32 + 1).next_power_of_two();
         let divider = match divider {
             1 | 2 | 4 | 8 | 16 | 64 | 256 | 1024 => divider,
             // There are a couple of gaps, so we round up to the next largest
@@ -189,10 +491,10 @@ impl TimerParams {
 
         let cycles: u32 = ticks / divider as u32;
 
-        if cycles > u16::max_value() as u32 {
+        if cycles > max {
             panic!(
-                "cycles {} is out of range for a 16 bit counter (timeout={})",
-                cycles, timeout.0
+                "cycles {} is out of range for a {} bit counter (timeout={})",
+                cycles, 32 - max.leading_zeros(), timeout.0
             );
         }
 
@@ -205,4 +507,9 @@ impl TimerParams {
 
 tc! {
     TimerCounter1: (TC1, tc1_, Tc1Tc2Clock),
+    TimerCounter2: (TC2, tc2_, Tc1Tc2Clock),
+}
+
+tc32! {
+    TimerCounter1_2: (TC1, TC2, tc1_tc2, tc1_, tc2_, Tc1Tc2Clock),
 }
@@ -2,12 +2,25 @@ use crate::{
     target_device,
     gpio,
     calibration,
+    events,
+    dmac::{
+        self,
+        channel::{Interrupts, TriggerSource},
+        circular::{CircularTransfer, DoubleBuffer, Half, Overrun},
+        transfer::ReadBuffer,
+    },
 };
 
+use crate::target_device::adc::{ctrlb::RESSEL_A, winctrl::WINMODE_A};
+
+#[cfg(feature = "unproven")]
+use crate::hal::adc::OneShot;
+
 use nb::{self, block};
 
 pub struct ADC {
     adc: target_device::ADC,
+    discard: bool,
 }
 
 impl ADC {
@@ -21,40 +34,22 @@ impl ADC {
 
         pm.apbcmask.modify(|_, w| w.adc_().set_bit());
 
-        while adc.status.read().syncbusy().bit_is_set() {
-            cortex_m::asm::nop();
-        }
-
-        adc.ctrlb.modify(|_, w| {
-            w.prescaler().div512();
-            w.ressel()._10bit()
-        });
-
-        adc.sampctrl.write(|w| unsafe {
-            w.samplen().bits(0b11_1111)
-        });
-
-        while adc.status.read().syncbusy().bit_is_set() {
-            cortex_m::asm::nop();
-        }
-
-        adc.inputctrl.modify(|_, w| w.muxneg().gnd());
+        let mut new = ADC {
+            adc,
+            discard: false,
+        };
 
-        adc.avgctrl.modify(|_, w| {
-            unsafe {
-                w.adjres().bits(0);
-            }
-            w.samplenum()._1()
-        });
+        new.prescaler(Prescaler::DIV512);
+        new.resolution(Resolution::_10Bit);
+        new.sample_length(0b11_1111);
+        new.gain(Gain::DIV2);
+        new.reference(Reference::INTVCC1);
 
-        // Reference
-        adc.inputctrl.modify(|_, w| w.gain().div2());
-        adc.refctrl.modify(|_, w| w.refsel().intvcc1());
+        new.sync();
+        new.adc.inputctrl.modify(|_, w| w.muxneg().gnd());
 
-        while adc.status.read().syncbusy().bit_is_set() {
-            cortex_m::asm::nop();
-        }
-        adc.ctrla.modify(|_, w| {
+        new.sync();
+        new.adc.ctrla.modify(|_, w| {
             w.enable().set_bit();
             w.runstdby().set_bit()
         });
@@ -63,17 +58,173 @@ impl ADC {
             First read
         */
 
-        let mut new = ADC {
-            adc,
-        };
-
         new.start(&mut InternalTemp);
         let _result = block!(new.wait());
         new.clear_ready_interrupt();
+        new.discard = false;
 
         new
     }
 
+    /// Select the voltage that a full scale result corresponds to. The
+    /// conversion after a change is discarded, as the datasheet requires.
+    pub fn reference(&mut self, reference: Reference) {
+        self.sync();
+        self.adc.refctrl.modify(|_, w| w.refsel().variant(reference));
+        self.discard = true;
+    }
+
+    /// Set the gain applied to the input before conversion.
+    pub fn gain(&mut self, gain: Gain) {
+        self.sync();
+        self.adc.inputctrl.modify(|_, w| w.gain().variant(gain));
+    }
+
+    /// Set the resolution of each result. This replaces any averaging set
+    /// with `samples`.
+    pub fn resolution(&mut self, resolution: Resolution) {
+        let (ressel, samples, adjres) = match resolution {
+            Resolution::_8Bit => (RESSEL_A::_8BIT, SampleCount::_1, 0),
+            Resolution::_10Bit => (RESSEL_A::_10BIT, SampleCount::_1, 0),
+            Resolution::_12Bit => (RESSEL_A::_12BIT, SampleCount::_1, 0),
+            // The 256 samples are shifted down to 16 bits by the hardware
+            Resolution::_16Bit => (RESSEL_A::_16BIT, SampleCount::_256, 0),
+        };
+
+        self.sync();
+        self.adc.ctrlb.modify(|_, w| w.ressel().variant(ressel));
+        self.sync();
+        self.adc.avgctrl.write(|w| unsafe {
+            w.samplenum().variant(samples);
+            w.adjres().bits(adjres)
+        });
+    }
+
+    /// Average `samples` conversions into each 12 bit result, trading
+    /// conversion rate for noise. `SampleCount::_1` turns averaging off.
+    pub fn samples(&mut self, samples: SampleCount) {
+        // The accumulated sum is shifted down by up to 4 bits with ADJRES,
+        // the hardware shifts sums wider than 16 bits down the rest of the way
+        let adjres = (u8::from(samples)).min(4);
+
+        self.sync();
+        self.adc.ctrlb.modify(|_, w| {
+            if samples == SampleCount::_1 {
+                w.ressel()._12bit()
+            } else {
+                w.ressel()._16bit()
+            }
+        });
+        self.sync();
+        self.adc.avgctrl.write(|w| unsafe {
+            w.samplenum().variant(samples);
+            w.adjres().bits(adjres)
+        });
+    }
+
+    /// Set the division of the ADC clock.
+    pub fn prescaler(&mut self, prescaler: Prescaler) {
+        self.sync();
+        self.adc.ctrlb.modify(|_, w| w.prescaler().variant(prescaler));
+    }
+
+    /// Extend sampling by `length` half ADC clock cycles, up to 63, for
+    /// sources with a high impedance.
+    pub fn sample_length(&mut self, length: u8) {
+        self.sync();
+        self.adc.sampctrl.write(|w| unsafe { w.samplen().bits(length.min(0b11_1111)) });
+    }
+
+    /// Convert the difference between `positive` and `negative`, blocking
+    /// until the result is ready. The result is signed, at the configured
+    /// resolution.
+    pub fn read_differential<P, N>(&mut self, positive: &mut P, _negative: &mut N) -> i16
+    where
+        P: Channel<Self, ID = Input>,
+        N: NegativeChannel<Self>,
+    {
+        self.sync();
+        self.adc.inputctrl.modify(|_, w| w.muxneg().variant(N::CHANNEL));
+        self.sync();
+        self.adc.ctrlb.modify(|_, w| w.diffmode().set_bit());
+
+        let result = self.convert(positive) as i16;
+
+        self.sync();
+        self.adc.ctrlb.modify(|_, w| w.diffmode().clear_bit());
+        self.sync();
+        self.adc.inputctrl.modify(|_, w| w.muxneg().gnd());
+
+        result
+    }
+
+    /// Measure the die temperature, in degrees Celsius, corrected with the
+    /// temperature log row. The ADC settings and the SYSCTRL voltage reference
+    /// are restored afterwards.
+    pub fn read_temperature_celsius(&mut self, sysctrl: &mut target_device::SYSCTRL) -> f32 {
+        let vref = sysctrl.vref.read().bits();
+        sysctrl.vref.modify(|_, w| w.tsen().set_bit());
+
+        self.sync();
+        let refctrl = self.adc.refctrl.read().bits();
+        let inputctrl = self.adc.inputctrl.read().bits();
+        let ctrlb = self.adc.ctrlb.read().bits();
+        let avgctrl = self.adc.avgctrl.read().bits();
+
+        // The log was taken against the 1V reference at 12 bits and unity gain
+        self.reference(Reference::INT1V);
+        self.gain(Gain::_1X);
+        self.resolution(Resolution::_12Bit);
+        let result = self.convert(&mut InternalTemp) as f32;
+
+        self.sync();
+        self.adc.refctrl.write(|w| unsafe { w.bits(refctrl) });
+        self.sync();
+        self.adc.inputctrl.write(|w| unsafe { w.bits(inputctrl) });
+        self.sync();
+        self.adc.ctrlb.write(|w| unsafe { w.bits(ctrlb) });
+        self.sync();
+        self.adc.avgctrl.write(|w| unsafe { w.bits(avgctrl) });
+        self.discard = true;
+        sysctrl.vref.write(|w| unsafe { w.bits(vref) });
+
+        // See 37.11.8.2.1 Temperature Sensor Calibration, first a coarse
+        // temperature against an ideal 1V reference, then a fine one against
+        // the reference interpolated at the coarse temperature
+        let log = calibration::temperature_log();
+        let room_v = log.room_adc as f32 * log.room_int1v / 4095.0;
+        let hot_v = log.hot_adc as f32 * log.hot_int1v / 4095.0;
+        let interpolate = |v: f32| {
+            log.room_temp + (log.hot_temp - log.room_temp) * (v - room_v) / (hot_v - room_v)
+        };
+
+        let coarse = interpolate(result / 4095.0);
+        let int1v = log.room_int1v + (log.hot_int1v - log.room_int1v) * (coarse - log.room_temp) / (log.hot_temp - log.room_temp);
+        interpolate(result * int1v / 4095.0)
+    }
+
+    /// Convert `input` against ground, blocking until the result is ready.
+    fn convert<PIN: Channel<Self, ID = Input>>(&mut self, input: &mut PIN) -> u16 {
+        if self.discard {
+            self.start(input);
+            let _ = block!(self.wait());
+            self.clear_ready_interrupt();
+            self.discard = false;
+        }
+
+        self.start(input);
+        let result = block!(self.wait()).unwrap();
+        self.clear_ready_interrupt();
+
+        result
+    }
+
+    fn sync(&self) {
+        while self.adc.status.read().syncbusy().bit_is_set() {
+            cortex_m::asm::nop();
+        }
+    }
+
     pub fn set_input<PIN: Channel<Self, ID = Input>>(&mut self, _input: &mut PIN) {
         while self.adc.status.read().syncbusy().bit_is_set() {
             cortex_m::asm::nop();
@@ -124,10 +275,264 @@ impl ADC {
         }
         self.adc.evctrl.modify(|_, w| w.startei().set_bit());
     }
+
+    /// Start a conversion on each event, returning the user to subscribe to
+    /// an event channel.
+    pub fn start_conversion_event(&mut self) -> events::UserHandle<target_device::ADC> {
+        self.enable_start_conversion_event();
+        events::UserHandle::new(events::User::ADC_START)
+    }
+
+    /// Enable the result ready event output, returning the generator to
+    /// publish on an event channel.
+    pub fn result_ready_event(&mut self) -> events::GeneratorHandle<target_device::ADC> {
+        while self.adc.status.read().syncbusy().bit_is_set() {
+            cortex_m::asm::nop();
+        }
+        self.adc.evctrl.modify(|_, w| w.resrdyeo().set_bit());
+        events::GeneratorHandle::new(events::Generator::ADC_RESRDY)
+    }
+
+    /// Compare every result against `window`, or stop comparing when
+    /// `None`. The window interrupt and event fire on each result that
+    /// matches.
+    pub fn window(&mut self, window: Option<Window>) {
+        let (mode, lower, upper) = match window {
+            None => (WINMODE_A::DISABLE, 0, 0),
+            Some(Window::Above(lower)) => (WINMODE_A::MODE1, lower, 0),
+            Some(Window::Below(upper)) => (WINMODE_A::MODE2, 0, upper),
+            Some(Window::Inside(lower, upper)) => (WINMODE_A::MODE3, lower, upper),
+            Some(Window::Outside(lower, upper)) => (WINMODE_A::MODE4, lower, upper),
+        };
+
+        self.sync();
+        self.adc.winlt.write(|w| unsafe { w.winlt().bits(lower) });
+        self.sync();
+        self.adc.winut.write(|w| unsafe { w.winut().bits(upper) });
+        self.sync();
+        self.adc.winctrl.write(|w| w.winmode().variant(mode));
+        self.sync();
+    }
+
+    pub fn enable_window_interrupt(&mut self) {
+        self.sync();
+        self.adc.intenset.write(|w| w.winmon().set_bit());
+    }
+
+    pub fn disable_window_interrupt(&mut self) {
+        self.sync();
+        self.adc.intenclr.write(|w| w.winmon().set_bit());
+    }
+
+    /// Whether a result has matched the window since the flag was cleared.
+    pub fn is_window_interrupt_set(&self) -> bool {
+        self.adc.intflag.read().winmon().bit_is_set()
+    }
+
+    pub fn clear_window_interrupt(&mut self) {
+        self.sync();
+        self.adc.intflag.write(|w| w.winmon().set_bit());
+    }
+
+    /// Enable the window monitor event output, returning the generator to
+    /// publish on an event channel.
+    pub fn window_event(&mut self) -> events::GeneratorHandle<target_device::ADC> {
+        self.sync();
+        self.adc.evctrl.modify(|_, w| w.winmoneo().set_bit());
+        events::GeneratorHandle::new(events::Generator::ADC_WINMON)
+    }
+
+    /// Convert `input` back to back until `stop_free_running`, for the
+    /// window monitor to watch without the CPU.
+    pub fn start_free_running<PIN: Channel<Self, ID = Input>>(&mut self, input: &mut PIN) {
+        self.set_input(input);
+
+        self.sync();
+        self.adc.ctrlb.modify(|_, w| w.freerun().set_bit());
+        self.sync();
+        self.adc.swtrig.write(|w| w.start().set_bit());
+    }
+
+    pub fn stop_free_running(&mut self) {
+        self.sync();
+        self.adc.ctrlb.modify(|_, w| w.freerun().clear_bit());
+        self.sync();
+        self.clear_ready_interrupt();
+    }
+
+    /// Sample `input` continuously into `buffer` using DMA `channel`, filling
+    /// each half in turn. Conversions are started as given by `start`.
+    pub fn stream<Ch, S, PIN>(
+        mut self,
+        input: &mut PIN,
+        start: StreamStart,
+        channel: Ch,
+        buffer: &'static mut DoubleBuffer<S>,
+        interrupts: Interrupts,
+    ) -> AdcStream<Ch, S>
+    where
+        Ch: dmac::Channel,
+        S: AsRef<[u16]> + AsMut<[u16]>,
+        PIN: Channel<Self, ID = Input>,
+    {
+        self.set_input(input);
+
+        while self.adc.status.read().syncbusy().bit_is_set() {
+            cortex_m::asm::nop();
+        }
+        self.adc.ctrlb.modify(|_, w| {
+            w.freerun().bit(start == StreamStart::FreeRunning)
+        });
+        if start == StreamStart::Event {
+            self.enable_start_conversion_event();
+        }
+
+        // Drop any result left over so the first beat is a fresh sample
+        self.result();
+        self.clear_ready_interrupt();
+
+        let transfer = CircularTransfer::start(
+            channel,
+            AdcResult { _private: () },
+            buffer,
+            TriggerSource::AdcResultReady,
+            interrupts,
+        );
+
+        if start == StreamStart::FreeRunning {
+            while self.adc.status.read().syncbusy().bit_is_set() {
+                cortex_m::asm::nop();
+            }
+            self.adc.swtrig.write(|w| w.start().set_bit());
+        }
+
+        AdcStream {
+            adc: self,
+            transfer,
+        }
+    }
 }
 
+/// How conversions are started while streaming
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StreamStart {
+    /// Convert back to back as fast as the ADC clock allows
+    FreeRunning,
+    /// Convert once per event on the start conversion event input, for
+    /// example a timer overflow routed through the event system
+    Event,
+}
+
+/// The ADC result register as a DMA source
+pub struct AdcResult {
+    _private: (),
+}
+
+unsafe impl ReadBuffer for AdcResult {
+    type Beat = u16;
+
+    fn dma_ptr(&mut self) -> *const u16 {
+        let adc = unsafe { &*target_device::ADC::ptr() };
+        &adc.result as *const _ as *const u16
+    }
+
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
+
+/// An ADC sampling continuously into a `DoubleBuffer`, as returned by
+/// `ADC::stream`.
+pub struct AdcStream<Ch, S: 'static> {
+    adc: ADC,
+    transfer: CircularTransfer<Ch, AdcResult, S>,
+}
+
+impl<Ch, S> AdcStream<Ch, S>
+where
+    Ch: dmac::Channel,
+    S: AsRef<[u16]> + AsMut<[u16]>,
+{
+    /// The half that was most recently filled, or `WouldBlock` if none has
+    /// filled since the last call. Read it with `read_half` before the other
+    /// half fills. Returns `Overrun` if a half was overwritten before it was
+    /// reported.
+    pub fn poll(&mut self) -> nb::Result<Half, Overrun> {
+        self.transfer.poll()
+    }
+
+    /// Copy the samples in one half of the buffer into `dest`, which must be
+    /// as long as a half. Returns `Overrun` if the half was being overwritten
+    /// during the copy.
+    pub fn read_half(&self, half: Half, dest: &mut [u16]) -> Result<(), Overrun> {
+        self.transfer.read_half(half, dest)
+    }
+
+    /// Stop sampling, then release the ADC, channel and buffer.
+    pub fn stop(self) -> (ADC, Ch, &'static mut DoubleBuffer<S>) {
+        let mut adc = self.adc;
+        let (channel, _, buffer) = self.transfer.stop();
+
+        while adc.adc.status.read().syncbusy().bit_is_set() {
+            cortex_m::asm::nop();
+        }
+        adc.adc.ctrlb.modify(|_, w| w.freerun().clear_bit());
+        while adc.adc.status.read().syncbusy().bit_is_set() {
+            cortex_m::asm::nop();
+        }
+        adc.adc.evctrl.modify(|_, w| w.startei().clear_bit());
+        adc.clear_ready_interrupt();
+
+        (adc, channel, buffer)
+    }
+}
+
+/// The positive input of a conversion
 pub type Input = target_device::adc::inputctrl::MUXPOS_A;
 
+/// The negative input of a differential conversion
+pub type Negative = target_device::adc::inputctrl::MUXNEG_A;
+
+/// The voltage that a full scale result corresponds to
+pub type Reference = target_device::adc::refctrl::REFSEL_A;
+
+/// The gain applied to the input before conversion
+pub type Gain = target_device::adc::inputctrl::GAIN_A;
+
+/// The number of conversions averaged into each result
+pub type SampleCount = target_device::adc::avgctrl::SAMPLENUM_A;
+
+/// The division of the ADC clock
+pub type Prescaler = target_device::adc::ctrlb::PRESCALER_A;
+
+/// The condition the window monitor checks each result against, the bounds
+/// are exclusive
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Window {
+    /// The result is above the lower bound
+    Above(u16),
+    /// The result is below the upper bound
+    Below(u16),
+    /// The result is between the lower and upper bound
+    Inside(u16, u16),
+    /// The result is not between the lower and upper bound
+    Outside(u16, u16),
+}
+
+/// The resolution of each result
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Resolution {
+    _8Bit,
+    _10Bit,
+    _12Bit,
+    /// 256 conversions oversampled and decimated into 16 bits
+    _16Bit,
+}
+
 pub trait Channel<ADC> {
     /// Channel ID type
     ///
@@ -142,11 +547,51 @@ pub trait Channel<ADC> {
     const CHANNEL: Self::ID;
 }
 
-pub struct InternalTemp;
+#[cfg(feature = "unproven")]
+impl<WORD, PIN> OneShot<ADC, WORD, PIN> for ADC
+where
+    WORD: From<u16>,
+    PIN: Channel<ADC, ID = Input> + crate::hal::adc::Channel<ADC, ID = Input>,
+{
+    type Error = ();
+
+    fn read(&mut self, pin: &mut PIN) -> nb::Result<WORD, Self::Error> {
+        Ok(self.convert(pin).into())
+    }
+}
+
+macro_rules! adc_internal {
+    ($($(#[$attr:meta])* $Type:ident: $chan:expr,)+) => {
+        $(
+            $(#[$attr])*
+            pub struct $Type;
+
+            impl Channel<ADC> for $Type {
+                type ID = Input;
+                const CHANNEL: Self::ID = $chan;
+            }
+
+            #[cfg(feature = "unproven")]
+            impl crate::hal::adc::Channel<ADC> for $Type {
+                type ID = Input;
+                fn channel() -> Input { $chan }
+            }
+        )+
+    }
+}
 
-impl Channel<ADC> for InternalTemp {
-    type ID = Input;
-    const CHANNEL: Self::ID = Input::TEMP;
+adc_internal! {
+    /// The temperature sensor, enabled with TSEN in the SYSCTRL VREF register
+    InternalTemp: Input::TEMP,
+    /// The 1.1V bandgap reference, enabled with BGOUTEN in the SYSCTRL VREF
+    /// register
+    Bandgap: Input::BANDGAP,
+    /// The core supply divided by 4
+    ScaledCoreSupply: Input::SCALEDCOREVCC,
+    /// The I/O supply divided by 4
+    ScaledIoSupply: Input::SCALEDIOVCC,
+    /// The DAC output
+    DacOutput: Input::DAC,
 }
 
 macro_rules! adc_pins {
@@ -156,6 +601,12 @@ macro_rules! adc_pins {
                 type ID = Input;
                 const CHANNEL: Self::ID = $chan;
             }
+
+            #[cfg(feature = "unproven")]
+            impl crate::hal::adc::Channel<ADC> for gpio::$pin<gpio::PfB> {
+                type ID = Input;
+                fn channel() -> Input { $chan }
+            }
         )+
     }
 }
@@ -186,3 +637,33 @@ adc_pins! {
     Pb8: Input::PIN2,
     Pb9: Input::PIN3,
 }
+
+/// An input that can be the negative side of a differential conversion
+pub trait NegativeChannel<ADC> {
+    const CHANNEL: Negative;
+}
+
+macro_rules! adc_negative_pins {
+    ($($pin:ident: $chan:expr,)+) => {
+        $(
+            impl NegativeChannel<ADC> for gpio::$pin<gpio::PfB> {
+                const CHANNEL: Negative = $chan;
+            }
+        )+
+    }
+}
+
+adc_negative_pins! {
+    Pa2: Negative::PIN0,
+    Pa3: Negative::PIN1,
+    Pa4: Negative::PIN4,
+    Pa5: Negative::PIN5,
+    Pa6: Negative::PIN6,
+    Pa7: Negative::PIN7,
+}
+
+#[cfg(any(feature = "samd21g18a", feature = "samd21j18a"))]
+adc_negative_pins! {
+    Pb8: Negative::PIN2,
+    Pb9: Negative::PIN3,
+}
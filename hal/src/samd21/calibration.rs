@@ -71,3 +71,38 @@ pub fn usb_transp_cal() -> u8 {
 pub fn usb_trim_cal() -> u8 {
     cal_with_errata(4, 23, 7, 7, 3) as u8
 }
+
+/// The temperature log row, the ADC readings of the temperature sensor taken
+/// at two temperatures during production. See 37.11.8.2.1 Temperature Sensor
+/// Calibration.
+#[derive(Clone, Copy, Debug)]
+pub struct TemperatureLog {
+    /// The room temperature in degrees Celsius
+    pub room_temp: f32,
+    /// The hot temperature in degrees Celsius
+    pub hot_temp: f32,
+    /// The 1V reference voltage at room temperature, in volts
+    pub room_int1v: f32,
+    /// The 1V reference voltage at the hot temperature, in volts
+    pub hot_int1v: f32,
+    /// The 12 bit ADC reading of the sensor at room temperature
+    pub room_adc: u16,
+    /// The 12 bit ADC reading of the sensor at the hot temperature
+    pub hot_adc: u16,
+}
+
+/// Returns the temperature log row, which follows the calibration area.
+pub fn temperature_log() -> TemperatureLog {
+    let temp = |int, dec| int as f32 + dec as f32 / 10.0;
+    // The reference is stored as its signed offset below 1V in mV
+    let int1v = |offset| 1.0 - (offset as u8 as i8) as f32 / 1000.0;
+
+    TemperatureLog {
+        room_temp: temp(cal(0x10, 0, 0xff), cal(0x10, 8, 0xf)),
+        hot_temp: temp(cal(0x10, 12, 0xff), cal(0x10, 20, 0xf)),
+        room_int1v: int1v(cal(0x10, 24, 0xff)),
+        hot_int1v: int1v(cal(0x14, 0, 0xff)),
+        room_adc: cal(0x14, 8, 0xfff) as u16,
+        hot_adc: cal(0x14, 20, 0xfff) as u16,
+    }
+}
@@ -0,0 +1,565 @@
+//! Input capture on the TC and TCC peripherals.
+//!
+//! A capture measures the period and pulse width of a signal that arrives on
+//! the counter's event input, usually from an EIC pin routed through the
+//! event system. The counter restarts at each period edge, the count at the
+//! period edge and at the pulse edge are captured into CC0 and CC1.
+//!
+//! ```ignore
+//! let mut capture = Capture::<TC3>::tc3_(&tc3_clock, peripherals.TC3, Prescaler::DIV16, CaptureMode::PeriodPulseWidth, &mut pm);
+//!
+//! pin.sense(&mut eic, Sense::HIGH);
+//! let generator = pin.event(&mut eic);
+//! event_sys.connect(Channel::Channel0, Path::Asynchronous, &generator, &capture);
+//!
+//! match capture.read() {
+//!     Ok(measurement) => measurement.frequency(capture.frequency()),
+//!     ...
+//! }
+//! ```
+use core::marker::PhantomData;
+use core::ops::Deref;
+
+use crate::{
+	target_device,
+	clock,
+	events,
+	time::Hertz,
+	timer::Count16,
+	dmac::{
+		self,
+		channel::{Interrupts, TriggerSource},
+		circular::{CircularTransfer, DoubleBuffer, Half, Overrun},
+		transfer::ReadBuffer,
+	},
+};
+
+use nb;
+
+/// The division of the TC clock that the counter ticks at
+pub type Prescaler = target_device::tc3::count16::ctrla::PRESCALER_A;
+
+/// The division of the TCC clock that the counter ticks at
+pub type TccPrescaler = target_device::tcc0::ctrla::PRESCALER_A;
+
+/// Which edges of the event input are captured into CC0 and CC1. The period
+/// edge is the rising edge of the event, invert the event in the EIC or use
+/// the other mode to measure low pulses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaptureMode {
+	/// The period is captured in CC0 and the pulse width in CC1
+	PeriodPulseWidth,
+	/// The pulse width is captured in CC0 and the period in CC1
+	PulseWidthPeriod,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Error {
+	/// The counter wrapped before the next period edge, the signal is slower
+	/// than the counter can measure or has stopped. The measurement that
+	/// spans the overflow is dropped.
+	Overflow,
+	/// A capture was overwritten before it was read.
+	Overrun,
+}
+
+/// A period and pulse width, in ticks of the counter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Measurement {
+	pub period: u32,
+	pub pulse_width: u32,
+}
+
+impl Measurement {
+	/// The frequency of the signal, given the frequency of the counter.
+	pub fn frequency(&self, tick: Hertz) -> Hertz {
+		Hertz(tick.0 / self.period.max(1))
+	}
+
+	/// The pulse width in microseconds, given the frequency of the counter.
+	pub fn pulse_width_micros(&self, tick: Hertz) -> u32 {
+		(self.pulse_width as u64 * 1_000_000 / tick.0.max(1) as u64) as u32
+	}
+
+	/// The part of the period the pulse lasts for, out of `scale`. For
+	/// example a scale of 100 gives the duty cycle in percent.
+	pub fn duty(&self, scale: u32) -> u32 {
+		(self.pulse_width as u64 * scale as u64 / self.period.max(1) as u64) as u32
+	}
+}
+
+fn divider(prescaler: u8) -> u32 {
+	match prescaler {
+		0 => 1,
+		1 => 2,
+		2 => 4,
+		3 => 8,
+		4 => 16,
+		5 => 64,
+		6 => 256,
+		_ => 1024,
+	}
+}
+
+/// A TC in 16-bit capture mode.
+pub struct Capture<TC> {
+	tc: TC,
+	freq: Hertz,
+	mode: CaptureMode,
+	// Set by an overflow, the next measurement spans it
+	stale: bool,
+}
+
+impl<TC> Capture<TC>
+where TC: Count16 {
+	/// The frequency the counter ticks at, the unit of each measurement.
+	pub fn frequency(&self) -> Hertz {
+		self.freq
+	}
+
+	/// The latest period and pulse width, once both have been captured since
+	/// the last read.
+	pub fn read(&mut self) -> nb::Result<Measurement, Error> {
+		let count = self.tc.count_16();
+		let flags = count.intflag.read();
+
+		if flags.ovf().bit_is_set() {
+			count.intflag.write(|w| w.ovf().set_bit());
+			self.stale = true;
+			return Err(nb::Error::Other(Error::Overflow));
+		}
+
+		if flags.err().bit_is_set() {
+			count.intflag.write(|w| w.err().set_bit());
+			return Err(nb::Error::Other(Error::Overrun));
+		}
+
+		if flags.mc0().bit_is_clear() || flags.mc1().bit_is_clear() {
+			return Err(nb::Error::WouldBlock);
+		}
+
+		// Reading the capture clears its flag
+		let cc0 = count.cc[0].read().cc().bits() as u32;
+		let cc1 = count.cc[1].read().cc().bits() as u32;
+
+		if self.stale {
+			self.stale = false;
+			return Err(nb::Error::WouldBlock);
+		}
+
+		Ok(measurement(self.mode, cc0, cc1))
+	}
+
+	/// Interrupt on each capture and on overflow, call `read` from the
+	/// handler.
+	pub fn enable_interrupt(&mut self) {
+		self.tc.count_16().intenset.write(|w| {
+			w.mc0().set_bit();
+			w.mc1().set_bit();
+			w.ovf().set_bit()
+		});
+	}
+
+	pub fn disable_interrupt(&mut self) {
+		self.tc.count_16().intenclr.write(|w| {
+			w.mc0().set_bit();
+			w.mc1().set_bit();
+			w.ovf().set_bit()
+		});
+	}
+
+	fn disable(&mut self) {
+		let count = self.tc.count_16();
+		count.ctrla.modify(|_, w| w.enable().clear_bit());
+		while count.status.read().syncbusy().bit_is_set() {
+			cortex_m::asm::nop();
+		}
+	}
+}
+
+fn measurement(mode: CaptureMode, cc0: u32, cc1: u32) -> Measurement {
+	match mode {
+		CaptureMode::PeriodPulseWidth => Measurement {
+			period: cc0,
+			pulse_width: cc1,
+		},
+		CaptureMode::PulseWidthPeriod => Measurement {
+			period: cc1,
+			pulse_width: cc0,
+		},
+	}
+}
+
+/// A TCC in capture mode, counting up to 24 bits on TCC0 and TCC1 and 16 bits
+/// on TCC2.
+pub struct TccCapture<TCC> {
+	tcc: TCC,
+	freq: Hertz,
+	mode: CaptureMode,
+	// Set by an overflow, the next measurement spans it
+	stale: bool,
+}
+
+impl<TCC> TccCapture<TCC>
+where TCC: Deref<Target = target_device::tcc0::RegisterBlock> {
+	/// The frequency the counter ticks at, the unit of each measurement.
+	pub fn frequency(&self) -> Hertz {
+		self.freq
+	}
+
+	/// The latest period and pulse width, once both have been captured since
+	/// the last read.
+	pub fn read(&mut self) -> nb::Result<Measurement, Error> {
+		let flags = self.tcc.intflag.read();
+
+		if flags.ovf().bit_is_set() {
+			self.tcc.intflag.write(|w| w.ovf().set_bit());
+			self.stale = true;
+			return Err(nb::Error::Other(Error::Overflow));
+		}
+
+		if flags.err().bit_is_set() {
+			self.tcc.intflag.write(|w| w.err().set_bit());
+			return Err(nb::Error::Other(Error::Overrun));
+		}
+
+		if flags.mc0().bit_is_clear() || flags.mc1().bit_is_clear() {
+			return Err(nb::Error::WouldBlock);
+		}
+
+		// Reading the capture clears its flag
+		let cc = self.tcc.cc();
+		let cc0 = cc[0].read().cc().bits();
+		let cc1 = cc[1].read().cc().bits();
+
+		if self.stale {
+			self.stale = false;
+			return Err(nb::Error::WouldBlock);
+		}
+
+		Ok(measurement(self.mode, cc0, cc1))
+	}
+
+	/// Interrupt on each capture and on overflow, call `read` from the
+	/// handler.
+	pub fn enable_interrupt(&mut self) {
+		self.tcc.intenset.write(|w| {
+			w.mc0().set_bit();
+			w.mc1().set_bit();
+			w.ovf().set_bit()
+		});
+	}
+
+	pub fn disable_interrupt(&mut self) {
+		self.tcc.intenclr.write(|w| {
+			w.mc0().set_bit();
+			w.mc1().set_bit();
+			w.ovf().set_bit()
+		});
+	}
+
+	fn disable(&mut self) {
+		self.tcc.ctrla.modify(|_, w| w.enable().clear_bit());
+		while self.tcc.syncbusy.read().enable().bit_is_set() {
+			cortex_m::asm::nop();
+		}
+	}
+}
+
+/// The CC0 register of a capture as a DMA source
+pub struct CaptureResult<TC> {
+	_tc: PhantomData<TC>,
+}
+
+/// A capture streaming CC0 into a `DoubleBuffer`, as returned by `stream`.
+/// CC0 holds the periods in `PeriodPulseWidth` mode and the pulse widths in
+/// `PulseWidthPeriod` mode.
+pub struct CaptureStream<C, TC, Ch, S: 'static> {
+	capture: C,
+	transfer: CircularTransfer<Ch, CaptureResult<TC>, S>,
+}
+
+impl<C, TC, Ch, S, B> CaptureStream<C, TC, Ch, S>
+where
+	Ch: dmac::Channel,
+	CaptureResult<TC>: ReadBuffer<Beat = B>,
+	S: AsRef<[B]> + AsMut<[B]>,
+	B: dmac::transfer::Beat,
+{
+	/// The half that was most recently filled, or `WouldBlock` if none has
+	/// filled since the last call. Read it with `read_half` before the other
+	/// half fills. Returns `Overrun` if a half was overwritten before it was
+	/// reported.
+	pub fn poll(&mut self) -> nb::Result<Half, Overrun> {
+		self.transfer.poll()
+	}
+
+	/// Copy the captures in one half of the buffer into `dest`, which must be
+	/// as long as a half. Returns `Overrun` if the half was being overwritten
+	/// during the copy.
+	pub fn read_half(&self, half: Half, dest: &mut [B]) -> Result<(), Overrun> {
+		self.transfer.read_half(half, dest)
+	}
+
+	/// Stop streaming, then release the capture, channel and buffer.
+	pub fn stop(self) -> (C, Ch, &'static mut DoubleBuffer<S>) {
+		let (channel, _, buffer) = self.transfer.stop();
+
+		(self.capture, channel, buffer)
+	}
+}
+
+macro_rules! capture {
+	($($TC:ident: ($pm:ident, $clock:ident, $user:ident, $trigger:ident),)+) => {
+		$(
+impl Capture<target_device::$TC> {
+	/// Configure the TC to capture from its event input. The capture starts
+	/// once an event generator is connected to it.
+	pub fn $pm(clock: &clock::$clock, tc: target_device::$TC, prescaler: Prescaler, mode: CaptureMode, pm: &mut target_device::PM) -> Self {
+		pm.apbcmask.modify(|_, w| w.$pm().set_bit());
+
+		let count = tc.count_16();
+
+		count.ctrla.modify(|_, w| w.enable().clear_bit());
+		while count.status.read().syncbusy().bit_is_set() {
+			cortex_m::asm::nop();
+		}
+		count.ctrla.write(|w| w.swrst().set_bit());
+		while count.status.read().syncbusy().bit_is_set() {
+			cortex_m::asm::nop();
+		}
+		// the SVD erroneously marks swrst as write-only, so we
+		// need to manually read the bit here
+		while count.ctrla.read().bits() & 1 != 0 {}
+
+		count.evctrl.write(|w| {
+			w.tcei().set_bit();
+			match mode {
+				CaptureMode::PeriodPulseWidth => w.evact().ppw(),
+				CaptureMode::PulseWidthPeriod => w.evact().pwp(),
+			}
+		});
+
+		count.ctrlc.write(|w| {
+			w.cpten0().set_bit();
+			w.cpten1().set_bit()
+		});
+		while count.status.read().syncbusy().bit_is_set() {
+			cortex_m::asm::nop();
+		}
+
+		count.ctrla.write(|w| {
+			w.prescaler().variant(prescaler);
+			w.enable().set_bit()
+		});
+		while count.status.read().syncbusy().bit_is_set() {
+			cortex_m::asm::nop();
+		}
+
+		Self {
+			tc,
+			freq: Hertz(clock.freq().0 / divider(prescaler.into())),
+			mode,
+			stale: false,
+		}
+	}
+
+	/// Stream CC0 into `buffer` using DMA `channel`, filling each half in
+	/// turn. The captures in CC1 are not read.
+	pub fn stream<Ch, S>(
+		mut self,
+		channel: Ch,
+		buffer: &'static mut DoubleBuffer<S>,
+		interrupts: Interrupts,
+	) -> CaptureStream<Self, target_device::$TC, Ch, S>
+	where
+		Ch: dmac::Channel,
+		S: AsRef<[u16]> + AsMut<[u16]>,
+	{
+		self.disable_interrupt();
+
+		// Drop any capture left over so the first beat is a fresh one
+		let count = self.tc.count_16();
+		count.intflag.write(|w| {
+			w.mc0().set_bit();
+			w.mc1().set_bit();
+			w.err().set_bit();
+			w.ovf().set_bit()
+		});
+
+		let transfer = CircularTransfer::start(
+			channel,
+			CaptureResult { _tc: PhantomData },
+			buffer,
+			TriggerSource::$trigger,
+			interrupts,
+		);
+
+		CaptureStream {
+			capture: self,
+			transfer,
+		}
+	}
+
+	/// Stop capturing and release the TC.
+	pub fn free(mut self) -> target_device::$TC {
+		self.disable();
+		self.tc
+	}
+}
+
+unsafe impl ReadBuffer for CaptureResult<target_device::$TC> {
+	type Beat = u16;
+
+	fn dma_ptr(&mut self) -> *const u16 {
+		let tc = unsafe { &*target_device::$TC::ptr() };
+		&tc.count16().cc[0] as *const _ as *const u16
+	}
+
+	fn incrementing(&self) -> bool {
+		false
+	}
+
+	fn buffer_len(&self) -> usize {
+		1
+	}
+}
+
+/// The capture's event input, subscribe it to the channel carrying the
+/// signal to measure
+impl events::EventUser for Capture<target_device::$TC> {
+	fn event_user(&self) -> events::User {
+		events::User::$user
+	}
+}
+		)+
+	}
+}
+
+macro_rules! tcc_capture {
+	($($TCC:ident: ($pm:ident, $clock:ident, $user:ident, $trigger:ident),)+) => {
+		$(
+impl TccCapture<target_device::$TCC> {
+	/// Configure the TCC to capture from its second event input. The capture
+	/// starts once an event generator is connected to it.
+	pub fn $pm(clock: &clock::$clock, tcc: target_device::$TCC, prescaler: TccPrescaler, mode: CaptureMode, pm: &mut target_device::PM) -> Self {
+		pm.apbcmask.modify(|_, w| w.$pm().set_bit());
+
+		tcc.ctrla.modify(|_, w| w.enable().clear_bit());
+		while tcc.syncbusy.read().enable().bit_is_set() {
+			cortex_m::asm::nop();
+		}
+		tcc.ctrla.write(|w| w.swrst().set_bit());
+		while tcc.syncbusy.read().swrst().bit_is_set() {
+			cortex_m::asm::nop();
+		}
+
+		// The event action and capture channels are enable protected
+		tcc.evctrl.write(|w| {
+			w.tcei1().set_bit();
+			match mode {
+				CaptureMode::PeriodPulseWidth => w.evact1().ppw(),
+				CaptureMode::PulseWidthPeriod => w.evact1().pwp(),
+			}
+		});
+
+		tcc.ctrla.write(|w| {
+			w.cpten0().set_bit();
+			w.cpten1().set_bit();
+			w.prescaler().variant(prescaler);
+			w.enable().set_bit()
+		});
+		while tcc.syncbusy.read().enable().bit_is_set() {
+			cortex_m::asm::nop();
+		}
+
+		Self {
+			tcc,
+			freq: Hertz(clock.freq().0 / divider(prescaler.into())),
+			mode,
+			stale: false,
+		}
+	}
+
+	/// Stream CC0 into `buffer` using DMA `channel`, filling each half in
+	/// turn. The captures in CC1 are not read.
+	pub fn stream<Ch, S>(
+		mut self,
+		channel: Ch,
+		buffer: &'static mut DoubleBuffer<S>,
+		interrupts: Interrupts,
+	) -> CaptureStream<Self, target_device::$TCC, Ch, S>
+	where
+		Ch: dmac::Channel,
+		S: AsRef<[u32]> + AsMut<[u32]>,
+	{
+		self.disable_interrupt();
+
+		// Drop any capture left over so the first beat is a fresh one
+		self.tcc.intflag.write(|w| {
+			w.mc0().set_bit();
+			w.mc1().set_bit();
+			w.err().set_bit();
+			w.ovf().set_bit()
+		});
+
+		let transfer = CircularTransfer::start(
+			channel,
+			CaptureResult { _tc: PhantomData },
+			buffer,
+			TriggerSource::$trigger,
+			interrupts,
+		);
+
+		CaptureStream {
+			capture: self,
+			transfer,
+		}
+	}
+
+	/// Stop capturing and release the TCC.
+	pub fn free(mut self) -> target_device::$TCC {
+		self.disable();
+		self.tcc
+	}
+}
+
+unsafe impl ReadBuffer for CaptureResult<target_device::$TCC> {
+	type Beat = u32;
+
+	fn dma_ptr(&mut self) -> *const u32 {
+		let tcc = unsafe { &*target_device::$TCC::ptr() };
+		&tcc.cc()[0] as *const _ as *const u32
+	}
+
+	fn incrementing(&self) -> bool {
+		false
+	}
+
+	fn buffer_len(&self) -> usize {
+		1
+	}
+}
+
+/// The capture's event input, subscribe it to the channel carrying the
+/// signal to measure
+impl events::EventUser for TccCapture<target_device::$TCC> {
+	fn event_user(&self) -> events::User {
+		events::User::$user
+	}
+}
+		)+
+	}
+}
+
+capture! {
+	TC3: (tc3_, Tcc2Tc3Clock, TC3, Tc3Mc0),
+	TC4: (tc4_, Tc4Tc5Clock, TC4, Tc4Mc0),
+	TC5: (tc5_, Tc4Tc5Clock, TC5, Tc5Mc0),
+}
+
+tcc_capture! {
+	TCC0: (tcc0_, Tcc0Tcc1Clock, TCC0_EV1, Tcc0Mc0),
+	TCC1: (tcc1_, Tcc0Tcc1Clock, TCC1_EV1, Tcc1Mc0),
+	TCC2: (tcc2_, Tcc2Tc3Clock, TCC2_EV1, Tcc2Mc0),
+}
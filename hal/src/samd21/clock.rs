@@ -312,6 +312,9 @@ clock_generator!(
     (rtc, RtcClock, RTC),
     (eic, EicClock, EIC),
     (adc, AdcClock, ADC),
+    (dac, DacClock, DAC),
+    (ac_dig, AcDigClock, AC_DIG),
+    (ac_ana, AcAnaClock, AC_ANA),
     (evch0, Evch0Clock, EVSYS_0),
     (evch1, Evch1Clock, EVSYS_1),
     (evch2, Evch2Clock, EVSYS_2),
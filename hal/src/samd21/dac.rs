@@ -0,0 +1,253 @@
+//! The 10 bit digital to analog converter.
+//!
+//! The DAC drives `VOUT` on PA02, and can feed the analog comparators and the
+//! ADC internally. Values are written straight to the output with `write`,
+//! or through the data buffer, which is moved to the output on each start
+//! conversion event. Fed by DMA from the empty trigger, the buffer plays back
+//! a waveform at the rate of the events, for example a timer overflow.
+//!
+//! ```ignore
+//! let mut dac = Dac::init(&mut peripherals.PM, dac_clock, peripherals.DAC, Reference::AVCC);
+//! let mut a0 = pins.a0.into_function_b(&mut pins.port);
+//! dac.enable(&mut a0);
+//! dac.write(512);
+//! ```
+use crate::{
+    target_device,
+    gpio,
+    events,
+    dmac::{
+        self,
+        channel::{Interrupts, TriggerSource},
+        transfer::WriteBuffer,
+        Transfer,
+    },
+};
+
+/// The voltage that the full scale output corresponds to
+pub type Reference = target_device::dac::ctrlb::REFSEL_A;
+
+/// The largest value the DAC converts
+pub const MAX: u16 = (1 << 10) - 1;
+
+/// A pin that the DAC can drive. You should not implement this trait
+/// yourself, only the implementations in this module make sense.
+pub trait DacPin {}
+
+impl DacPin for gpio::Pa2<gpio::PfB> {}
+
+pub struct Dac {
+    dac: target_device::DAC,
+}
+
+impl Dac {
+    /// Configure the DAC with its outputs disabled.
+    pub fn init(pm: &mut target_device::PM, _clock: crate::clock::DacClock, dac: target_device::DAC, reference: Reference) -> Self {
+        pm.apbcmask.modify(|_, w| w.dac_().set_bit());
+
+        let new = Dac { dac };
+
+        new.sync();
+        new.dac.ctrla.write(|w| w.swrst().set_bit());
+        new.sync();
+
+        new.dac.ctrlb.write(|w| w.refsel().variant(reference));
+
+        new.sync();
+        new.dac.ctrla.write(|w| w.enable().set_bit());
+        new.sync();
+
+        new
+    }
+
+    /// Select the voltage that the full scale output corresponds to.
+    pub fn reference(&mut self, reference: Reference) {
+        self.sync();
+        self.dac.ctrlb.modify(|_, w| w.refsel().variant(reference));
+    }
+
+    /// Drive `pin` with the output.
+    pub fn enable<PIN: DacPin>(&mut self, _pin: &mut PIN) {
+        self.sync();
+        self.dac.ctrlb.modify(|_, w| w.eoen().set_bit());
+    }
+
+    /// Stop driving the pin, it is left floating.
+    pub fn disable(&mut self) {
+        self.sync();
+        self.dac.ctrlb.modify(|_, w| w.eoen().clear_bit());
+    }
+
+    /// Connect the output to the analog comparators and the ADC.
+    pub fn enable_internal(&mut self, enable: bool) {
+        self.sync();
+        self.dac.ctrlb.modify(|_, w| w.ioen().bit(enable));
+    }
+
+    /// Convert `value`, of up to 10 bits, blocking until the output has
+    /// been updated.
+    pub fn write(&mut self, value: u16) {
+        self.sync();
+        self.dac.data.write(|w| unsafe { w.data().bits(value.min(MAX)) });
+        self.sync();
+    }
+
+    /// Load `value` into the data buffer, it is converted on the next start
+    /// conversion event.
+    pub fn write_buffered(&mut self, value: u16) {
+        self.sync();
+        self.dac.databuf.write(|w| unsafe { w.databuf().bits(value.min(MAX)) });
+    }
+
+    /// Convert the data buffer on each event, returning the user to
+    /// subscribe to an event channel.
+    pub fn start_conversion_event(&mut self) -> events::UserHandle<target_device::DAC> {
+        self.configure(|dac| {
+            dac.evctrl.modify(|_, w| w.startei().set_bit());
+        });
+        events::UserHandle::new(events::User::DAC_START)
+    }
+
+    /// Enable the data buffer empty event output, returning the generator to
+    /// publish on an event channel.
+    pub fn empty_event(&mut self) -> events::GeneratorHandle<target_device::DAC> {
+        self.configure(|dac| {
+            dac.evctrl.modify(|_, w| w.emptyeo().set_bit());
+        });
+        events::GeneratorHandle::new(events::Generator::DAC_EMPTY)
+    }
+
+    /// Whether the data buffer has been converted and can take a new value.
+    pub fn is_empty(&self) -> bool {
+        self.dac.intflag.read().empty().bit_is_set()
+    }
+
+    /// Whether a start conversion event arrived before the data buffer was
+    /// written.
+    pub fn is_underrun(&self) -> bool {
+        self.dac.intflag.read().underrun().bit_is_set()
+    }
+
+    pub fn clear_underrun(&mut self) {
+        self.dac.intflag.write(|w| w.underrun().set_bit());
+    }
+
+    /// Play `samples` through the data buffer with DMA `channel`, one per
+    /// start conversion event. The events should be connected first, see
+    /// `start_conversion_event`.
+    ///
+    /// Panics if there are fewer than two samples.
+    pub fn play<Ch>(
+        mut self,
+        samples: &'static [u16],
+        channel: Ch,
+        interrupts: Interrupts,
+    ) -> DacPlayback<Ch>
+    where
+        Ch: dmac::Channel,
+    {
+        // Convert the first sample on the first event, the rest are loaded
+        // as each is converted
+        assert!(samples.len() >= 2, "fewer than two samples to play");
+        let (first, rest) = samples.split_at(1);
+        self.write_buffered(first[0]);
+
+        let transfer = Transfer::start(
+            channel,
+            rest,
+            DataBuffer { _private: () },
+            TriggerSource::DacEmpty,
+            interrupts,
+        );
+
+        DacPlayback {
+            dac: self,
+            samples,
+            transfer,
+        }
+    }
+
+    /// Disable the DAC and release it.
+    pub fn free(self) -> target_device::DAC {
+        self.sync();
+        self.dac.ctrla.modify(|_, w| w.enable().clear_bit());
+        self.sync();
+
+        self.dac
+    }
+
+    /// EVCTRL can only be written while the DAC is disabled, it is enabled
+    /// again afterwards.
+    fn configure<F: FnOnce(&target_device::DAC)>(&mut self, f: F) {
+        self.sync();
+        self.dac.ctrla.modify(|_, w| w.enable().clear_bit());
+        self.sync();
+
+        f(&self.dac);
+
+        self.dac.ctrla.modify(|_, w| w.enable().set_bit());
+        self.sync();
+    }
+
+    fn sync(&self) {
+        while self.dac.status.read().syncbusy().bit_is_set() {
+            cortex_m::asm::nop();
+        }
+    }
+}
+
+/// The DAC data buffer register as a DMA destination
+pub struct DataBuffer {
+    _private: (),
+}
+
+unsafe impl WriteBuffer for DataBuffer {
+    type Beat = u16;
+
+    fn dma_ptr(&mut self) -> *mut u16 {
+        let dac = unsafe { &*target_device::DAC::ptr() };
+        &dac.databuf as *const _ as *mut u16
+    }
+
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
+
+/// A DAC playing samples from memory, as returned by `Dac::play`.
+pub struct DacPlayback<Ch> {
+    dac: Dac,
+    samples: &'static [u16],
+    transfer: Transfer<Ch, &'static [u16], DataBuffer>,
+}
+
+impl<Ch> DacPlayback<Ch>
+where
+    Ch: dmac::Channel,
+{
+    /// Whether every sample has been loaded into the data buffer, the last
+    /// is converted on the next event.
+    pub fn is_complete(&self) -> bool {
+        self.transfer.is_complete()
+    }
+
+    /// Block until every sample has been loaded, then release the DAC,
+    /// channel and samples. If the DMAC stopped with an error some samples
+    /// were not played.
+    pub fn wait(self) -> Result<(Dac, Ch, &'static [u16]), (dmac::transfer::Error, Dac, Ch, &'static [u16])> {
+        match self.transfer.wait() {
+            Ok((channel, _, _)) => Ok((self.dac, channel, self.samples)),
+            Err((error, channel, _, _)) => Err((error, self.dac, channel, self.samples)),
+        }
+    }
+
+    /// Stop playing, then release the DAC, channel and samples.
+    pub fn stop(self) -> (Dac, Ch, &'static [u16]) {
+        let (channel, _, _) = self.transfer.stop();
+        (self.dac, channel, self.samples)
+    }
+}
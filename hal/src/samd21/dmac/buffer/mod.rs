@@ -17,7 +17,7 @@ pub mod serial;
 pub use serial::Serial;
 
 pub struct ConstBuffer<A, B> {
-    // Descriptors, one fewer than given due to dmac::BASE_DESCRIPTORS
+    // Descriptors, one fewer than given as the first is the channel's own
     descriptors: MaybeUninit<A>,
     // Backing Store, X bytes per descriptor
     data: MaybeUninit<B>,
@@ -72,13 +72,13 @@ where
     /// Initialise the descriptors for this static buffer
     ///
     /// descriptors: must have enough space to hold (backing.len() / X) - 1 descriptors
-    pub fn configure<'a, F>(
+    pub fn configure<'a, C, F>(
         &mut self,
-        _dmac: &mut dmac::DMAC,
-        channel: dmac::Channel,
+        channel: &mut C,
         mut f: F,
     ) -> Result<(Producer<'a, N, X>, Consumer<'a, N, X>), ()>
     where
+        C: dmac::Channel,
         F: FnMut(*mut u8, u16, *const dmac::Descriptor) -> dmac::Descriptor,
     {
         self.0.configured.take().expect("only configured once");
@@ -90,17 +90,19 @@ where
         let data_ptr: *mut u8 = self.0.data.as_mut_ptr() as *mut u8;
         let _data_len = <N as Mul<X>>::Output::to_usize();
 
+        let first = channel.descriptor_ptr();
+
         unsafe {
             // First descriptor, perhaps only descriptor
-            dmac::BASE_DESCRIPTORS[channel as usize] = f(
+            channel.set_descriptor(f(
                 data_ptr,
                 X::to_u16(),
                 if descriptors_len == 0 {
-                    &dmac::BASE_DESCRIPTORS[channel as usize]
+                    first
                 } else {
                     descriptors_ptr
                 },
-            );
+            ));
 
             for desc_idx in 0usize..descriptors_len {
                 let dst = data_ptr.offset(X::to_isize() * (desc_idx as isize + 1));
@@ -111,7 +113,7 @@ where
                     descriptors_ptr.offset((desc_idx + 1) as isize)
                 } else {
                     // Loop to the first descriptor
-                    &dmac::BASE_DESCRIPTORS[channel as usize] as *const _
+                    first
                 };
 
                 *descriptors_ptr.offset(desc_idx as isize) = f(dst, count, next);
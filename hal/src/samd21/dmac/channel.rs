@@ -1,47 +1,279 @@
+use core::ptr;
+
 use crate::{
 	target_device,
 	events,
 };
 
-#[allow(unused)]
-#[derive(Debug, Clone, Copy)]
-pub enum Channel {
-	Channel0 = 0,
-	Channel1 = 1,
-	Channel2 = 2,
-	Channel3 = 3,
-	Channel4 = 4,
-	Channel5 = 5,
-	Channel6 = 6,
-	Channel7 = 7,
-	Channel8 = 8,
-	Channel9 = 9,
-	Channel10 = 10,
-	Channel11 = 11,
+use super::{
+	Descriptor,
+	BASE_DESCRIPTORS,
+	WRITEBACK_DESCRIPTORS,
+};
+
+/// Run `f` with `CHID` pointing at the given channel.
+///
+/// The channel registers are shared between all channels through the `CHID`
+/// indirection, so this must not be interrupted by another channel.
+fn with_channel<F, R>(id: u8, f: F) -> R
+where F: FnOnce(&target_device::dmac::RegisterBlock) -> R {
+	cortex_m::interrupt::free(|_| {
+		let dmac = unsafe { &*target_device::DMAC::ptr() };
+
+		dmac.chid.write(|w| unsafe {
+			w.bits(id)
+		});
+
+		f(dmac)
+	})
+}
+
+mod sealed {
+	/// Keeps `Channel`, `EventInput` and `EventOutput` to the channels
+	/// defined in this module.
+	pub trait Sealed {}
 }
 
-impl Channel {
-	pub fn event_generator(&self) -> events::Generator {
-		match self {
-			&Channel::Channel0 => events::Generator::DMAC_CH0,
-			&Channel::Channel1 => events::Generator::DMAC_CH1,
-			&Channel::Channel2 => events::Generator::DMAC_CH2,
-			&Channel::Channel3 => events::Generator::DMAC_CH3,
-			_ => panic!("channel {:?} does not support events", self),
+/// A DMAC channel obtained from `DMAC::split`. This trait is sealed, only
+/// the channels in this module implement it.
+pub trait Channel: sealed::Sealed {
+	const ID: u8;
+
+	/// Configure the trigger and interrupts for this channel, the channel is
+	/// left disabled.
+	fn configure(&mut self, source: TriggerSource, action: Action, interrupts: Interrupts) {
+		with_channel(Self::ID, |dmac| {
+			dmac.chctrla.modify(|_, w| {
+				w.enable().clear_bit()
+			});
+			while dmac.chctrla.read().enable().bit_is_set() {
+				cortex_m::asm::nop();
+			}
+
+			dmac.chctrlb.modify(|_, w| {
+				w.lvl().lvl0();
+
+				unsafe { w.trigsrc().bits(source as u8); }
+				w.trigact().variant(action)
+			});
+
+			dmac.chintenclr.write(|w| {
+				w.susp().set_bit();
+				w.tcmpl().set_bit();
+				w.terr().set_bit()
+			});
+			dmac.chintenset.write(|w| {
+				w.susp().bit(interrupts.suspend);
+				w.tcmpl().bit(interrupts.transfer_complete);
+				w.terr().bit(interrupts.transfer_error)
+			});
+		});
+	}
+
+	fn enable(&mut self) {
+		with_channel(Self::ID, |dmac| {
+			dmac.chctrla.modify(|_, w| {
+				w.enable().set_bit()
+			});
+		});
+	}
+
+	fn disable(&mut self) {
+		with_channel(Self::ID, |dmac| {
+			dmac.chctrla.modify(|_, w| {
+				w.enable().clear_bit()
+			});
+			while dmac.chctrla.read().enable().bit_is_set() {
+				cortex_m::asm::nop();
+			}
+		});
+	}
+
+	fn is_enabled(&self) -> bool {
+		with_channel(Self::ID, |dmac| {
+			dmac.chctrla.read().enable().bit_is_set()
+		})
+	}
+
+	fn trigger(&mut self) {
+		let dmac = unsafe { &*target_device::DMAC::ptr() };
+		dmac.swtrigctrl.write(|w| unsafe {
+			w.bits(1 << Self::ID)
+		});
+	}
+
+	/// Whether the DMAC disabled this channel because of a transfer error.
+	fn is_failed(&self) -> bool {
+		with_channel(Self::ID, |dmac| {
+			dmac.chintflag.read().terr().bit_is_set()
+		})
+	}
+
+	/// Clear this channel's interrupt flags, returning the flags that were
+	/// set.
+	fn clear_interrupts(&mut self) -> Interrupts {
+		with_channel(Self::ID, |dmac| {
+			let flags = dmac.chintflag.read();
+
+			let interrupts = Interrupts {
+				suspend: flags.susp().bit_is_set(),
+				transfer_complete: flags.tcmpl().bit_is_set(),
+				transfer_error: flags.terr().bit_is_set(),
+			};
+
+			dmac.chintflag.write(|w| {
+				w.susp().bit(interrupts.suspend);
+				w.tcmpl().bit(interrupts.transfer_complete);
+				w.terr().bit(interrupts.transfer_error)
+			});
+
+			interrupts
+		})
+	}
+
+	/// The first descriptor of this channel, the DMAC fetches it from here
+	/// when the channel is triggered.
+	fn descriptor_ptr(&self) -> *const Descriptor {
+		unsafe { (ptr::addr_of!(BASE_DESCRIPTORS) as *const Descriptor).add(Self::ID as usize) }
+	}
+
+	/// Replace the first descriptor of this channel.
+	///
+	/// This is unsafe because the DMAC will read from and write to whatever
+	/// addresses the descriptor (and any descriptors it links to) point at.
+	/// Those addresses must remain valid until the transfer has completed.
+	unsafe fn set_descriptor(&mut self, descriptor: Descriptor) {
+		ptr::write_volatile((ptr::addr_of_mut!(BASE_DESCRIPTORS) as *mut Descriptor).add(Self::ID as usize), descriptor);
+	}
+
+	/// The state of the in progress descriptor, `btcnt` is the number of beats
+	/// remaining.
+	fn writeback(&self) -> Descriptor {
+		unsafe {
+			ptr::read_volatile((ptr::addr_of!(WRITEBACK_DESCRIPTORS) as *const Descriptor).add(Self::ID as usize))
 		}
 	}
+}
 
-	pub fn event_user(&self) -> events::User {
-		match self {
-			&Channel::Channel0 => events::User::DMAC_CH0,
-			&Channel::Channel1 => events::User::DMAC_CH1,
-			&Channel::Channel2 => events::User::DMAC_CH2,
-			&Channel::Channel3 => events::User::DMAC_CH3,
-			_ => panic!("channel {:?} does not support events", self),
+/// A DMAC channel that can be triggered or controlled from the event system.
+/// Only channels 0-3 are connected to the event system.
+pub trait EventInput: Channel + events::EventUser + sealed::Sealed {
+	/// Must be called while the channel is disabled.
+	fn enable_event_input(&mut self, action: EventInputAction) {
+		with_channel(Self::ID, |dmac| {
+			dmac.chctrlb.modify(|_, w| {
+				w.evie().set_bit();
+				w.evact().variant(action)
+			});
+		});
+	}
+
+	fn disable_event_input(&mut self) {
+		with_channel(Self::ID, |dmac| {
+			dmac.chctrlb.modify(|_, w| {
+				w.evie().clear_bit()
+			});
+		});
+	}
+}
+
+/// A DMAC channel that can generate events as selected by `Btctrl::evosel`.
+/// Only channels 0-3 are connected to the event system.
+pub trait EventOutput: Channel + events::EventGenerator + sealed::Sealed {
+	/// Must be called while the channel is disabled.
+	fn enable_event_output(&mut self) {
+		with_channel(Self::ID, |dmac| {
+			dmac.chctrlb.modify(|_, w| {
+				w.evoe().set_bit()
+			});
+		});
+	}
+
+	fn disable_event_output(&mut self) {
+		with_channel(Self::ID, |dmac| {
+			dmac.chctrlb.modify(|_, w| {
+				w.evoe().clear_bit()
+			});
+		});
+	}
+}
+
+macro_rules! channels {
+	($($Ch:ident: ($field:ident, $num:expr),)+) => {
+		/// The DMAC channels, as returned by `DMAC::split`.
+		pub struct Channels {
+			$(
+				pub $field: $Ch,
+			)+
+		}
+
+		impl Channels {
+			pub(super) fn new() -> Self {
+				Channels {
+					$(
+						$field: $Ch { _private: () },
+					)+
+				}
+			}
 		}
+
+		$(
+			pub struct $Ch {
+				_private: (),
+			}
+
+			impl sealed::Sealed for $Ch {}
+
+			impl Channel for $Ch {
+				const ID: u8 = $num;
+			}
+		)+
 	}
 }
 
+macro_rules! event_channels {
+	($($Ch:ident: ($user:ident, $generator:ident),)+) => {
+		$(
+			impl events::EventUser for $Ch {
+				fn event_user(&self) -> events::User {
+					events::User::$user
+				}
+			}
+
+			impl events::EventGenerator for $Ch {
+				fn event_generator(&self) -> events::Generator {
+					events::Generator::$generator
+				}
+			}
+
+			impl EventInput for $Ch {}
+			impl EventOutput for $Ch {}
+		)+
+	}
+}
+
+channels! {
+	Ch0: (ch0, 0),
+	Ch1: (ch1, 1),
+	Ch2: (ch2, 2),
+	Ch3: (ch3, 3),
+	Ch4: (ch4, 4),
+	Ch5: (ch5, 5),
+	Ch6: (ch6, 6),
+	Ch7: (ch7, 7),
+	Ch8: (ch8, 8),
+	Ch9: (ch9, 9),
+	Ch10: (ch10, 10),
+	Ch11: (ch11, 11),
+}
+
+event_channels! {
+	Ch0: (DMAC_CH0, DMAC_CH0),
+	Ch1: (DMAC_CH1, DMAC_CH1),
+	Ch2: (DMAC_CH2, DMAC_CH2),
+	Ch3: (DMAC_CH3, DMAC_CH3),
+}
+
 #[allow(unused)]
 #[derive(Clone, Copy)]
 pub enum TriggerSource {
@@ -66,24 +298,53 @@ pub enum TriggerSource {
 	Sercom5Rx = 0xB,
 	Sercom5Tx = 0xC,
 
+	Tcc0Ovf = 0x0D,
+	Tcc0Mc0 = 0x0E,
+	Tcc0Mc1 = 0x0F,
+	Tcc0Mc2 = 0x10,
+	Tcc0Mc3 = 0x11,
+
+	Tcc1Ovf = 0x12,
+	Tcc1Mc0 = 0x13,
+	Tcc1Mc1 = 0x14,
+
+	Tcc2Ovf = 0x15,
+	Tcc2Mc0 = 0x16,
+	Tcc2Mc1 = 0x17,
+
 	/// TimerCounter 3 overflow
 	Tc3Ovf = 0x18,
+	Tc3Mc0 = 0x19,
+	Tc3Mc1 = 0x1A,
 
 	Tc4Ovf = 0x1B,
+	Tc4Mc0 = 0x1C,
+	Tc4Mc1 = 0x1D,
+
+	Tc5Ovf = 0x1E,
+	Tc5Mc0 = 0x1F,
+	Tc5Mc1 = 0x20,
+
+	Tc6Ovf = 0x21,
+	Tc6Mc0 = 0x22,
+	Tc6Mc1 = 0x23,
+
+	Tc7Ovf = 0x24,
+	Tc7Mc0 = 0x25,
+	Tc7Mc1 = 0x26,
 
 	/// ADC Result Ready
 	AdcResultReady = 0x27,
+
+	/// DAC Data Buffer Empty
+	DacEmpty = 0x28,
 }
 
 pub type Action = target_device::dmac::chctrlb::TRIGACT_A;
 
-pub struct Events {
-	pub output: bool,
-	pub input: Option<EventInputAction>,
-}
-
 pub type EventInputAction = target_device::dmac::chctrlb::EVACT_A;
 
+#[derive(Clone, Copy, Default)]
 pub struct Interrupts {
 	pub suspend: bool,
 	pub transfer_complete: bool,
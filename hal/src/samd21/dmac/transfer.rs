@@ -0,0 +1,303 @@
+use core::sync::atomic::{
+	compiler_fence,
+	Ordering,
+};
+
+use super::{
+	channel::{
+		Action,
+		Channel,
+		Interrupts,
+		TriggerSource,
+	},
+	descriptor::{
+		Beatsize,
+		BlockAction,
+		Btctrl,
+		Descriptor,
+		EventOutputAction,
+		Stepsel,
+		Stepsize,
+	},
+};
+
+/// A type that can be moved in a single DMA beat. You should not implement
+/// this trait for yourself; only the implementations in this module make
+/// sense.
+pub unsafe trait Beat: Copy {
+	const BEATSIZE: Beatsize;
+}
+
+macro_rules! beat {
+	($($Type:ty: $size:ident,)+) => {
+		$(
+			unsafe impl Beat for $Type {
+				const BEATSIZE: Beatsize = Beatsize::$size;
+			}
+		)+
+	}
+}
+
+beat! {
+	u8: Byte,
+	i8: Byte,
+	u16: HalfWord,
+	i16: HalfWord,
+	u32: Word,
+	i32: Word,
+}
+
+/// Something the DMAC can read beats from: memory that lives for the whole
+/// transfer or a peripheral data register.
+///
+/// This is unsafe to implement because the DMAC will read `buffer_len` beats
+/// from `dma_ptr`, which must stay valid while the implementor is owned by a
+/// `Transfer`.
+pub unsafe trait ReadBuffer {
+	type Beat: Beat;
+
+	/// The address of the first beat
+	fn dma_ptr(&mut self) -> *const Self::Beat;
+
+	/// Whether the address advances after each beat, peripheral registers
+	/// do not.
+	fn incrementing(&self) -> bool;
+
+	/// The number of beats, for a non incrementing buffer this is 1
+	fn buffer_len(&self) -> usize;
+}
+
+/// Something the DMAC can write beats to: memory that lives for the whole
+/// transfer or a peripheral data register.
+///
+/// This is unsafe to implement because the DMAC will write `buffer_len` beats
+/// to `dma_ptr`, which must stay valid and unaliased while the implementor is
+/// owned by a `Transfer`.
+pub unsafe trait WriteBuffer {
+	type Beat: Beat;
+
+	/// The address of the first beat
+	fn dma_ptr(&mut self) -> *mut Self::Beat;
+
+	/// Whether the address advances after each beat, peripheral registers
+	/// do not.
+	fn incrementing(&self) -> bool;
+
+	/// The number of beats, for a non incrementing buffer this is 1
+	fn buffer_len(&self) -> usize;
+}
+
+unsafe impl<T: Beat> ReadBuffer for &'static [T] {
+	type Beat = T;
+
+	fn dma_ptr(&mut self) -> *const T {
+		self.as_ptr()
+	}
+
+	fn incrementing(&self) -> bool {
+		true
+	}
+
+	fn buffer_len(&self) -> usize {
+		self.len()
+	}
+}
+
+unsafe impl<T: Beat> ReadBuffer for &'static mut [T] {
+	type Beat = T;
+
+	fn dma_ptr(&mut self) -> *const T {
+		self.as_ptr()
+	}
+
+	fn incrementing(&self) -> bool {
+		true
+	}
+
+	fn buffer_len(&self) -> usize {
+		self.len()
+	}
+}
+
+unsafe impl<T: Beat> WriteBuffer for &'static mut [T] {
+	type Beat = T;
+
+	fn dma_ptr(&mut self) -> *mut T {
+		self.as_mut_ptr()
+	}
+
+	fn incrementing(&self) -> bool {
+		true
+	}
+
+	fn buffer_len(&self) -> usize {
+		self.len()
+	}
+}
+
+/// A single value, repeated into every beat of the destination
+unsafe impl<T: Beat> ReadBuffer for &'static T {
+	type Beat = T;
+
+	fn dma_ptr(&mut self) -> *const T {
+		*self as *const T
+	}
+
+	fn incrementing(&self) -> bool {
+		false
+	}
+
+	fn buffer_len(&self) -> usize {
+		1
+	}
+}
+
+/// Why a transfer stopped before moving every beat
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+	/// The DMAC hit a bus error on the source or destination, or fetched an
+	/// invalid descriptor, and disabled the channel
+	Transfer,
+}
+
+/// Address to put in a descriptor, the DMAC expects the end address of an
+/// incrementing buffer.
+pub(super) fn descriptor_address<T>(ptr: *const T, incrementing: bool, len: usize) -> *const u8 {
+	if incrementing {
+		ptr.wrapping_add(len) as *const u8
+	} else {
+		ptr as *const u8
+	}
+}
+
+/// A one-shot DMA transfer that owns its channel, source and destination
+/// until it has finished.
+///
+/// Transfers triggered by `TriggerSource::Software` move the whole block at
+/// once, memory to memory. Any other trigger moves one beat per trigger which
+/// is what a peripheral data register expects.
+pub struct Transfer<Ch, Src, Dst> {
+	channel: Ch,
+	source: Src,
+	destination: Dst,
+}
+
+impl<Ch, Src, Dst, B> Transfer<Ch, Src, Dst>
+where
+	Ch: Channel,
+	Src: ReadBuffer<Beat = B>,
+	Dst: WriteBuffer<Beat = B>,
+	B: Beat,
+{
+	/// Configure the channel and start the transfer.
+	///
+	/// Panics if both buffers are incrementing but differ in length, or if the
+	/// transfer is empty or longer than 65535 beats.
+	pub fn start(mut channel: Ch, mut source: Src, mut destination: Dst, trigger: TriggerSource, interrupts: Interrupts) -> Self {
+		let src_inc = source.incrementing();
+		let dst_inc = destination.incrementing();
+
+		let len = match (src_inc, dst_inc) {
+			(true, true) => {
+				assert!(source.buffer_len() == destination.buffer_len(), "source and destination lengths differ");
+				source.buffer_len()
+			},
+			(true, false) => source.buffer_len(),
+			(false, true) => destination.buffer_len(),
+			(false, false) => 1,
+		};
+		assert!(len > 0, "transfer is empty");
+		assert!(len <= u16::max_value() as usize, "transfer is longer than 65535 beats");
+
+		let descriptor = Descriptor {
+			btctrl: {
+				let mut btctrl = Btctrl(0);
+				btctrl.set_stepsize(Stepsize::X1);
+				btctrl.set_stepsel(Stepsel::StepSrc);
+				btctrl.set_dstinc(dst_inc);
+				btctrl.set_srcinc(src_inc);
+				btctrl.set_beatsize(B::BEATSIZE);
+				btctrl.set_blockact(BlockAction::None);
+				btctrl.set_evosel(EventOutputAction::None);
+				btctrl.set_valid(true);
+				btctrl
+			},
+			btcnt: len as u16,
+			srcaddr: descriptor_address(source.dma_ptr(), src_inc, len),
+			dstaddr: descriptor_address(destination.dma_ptr(), dst_inc, len) as *mut u8,
+			.. Default::default()
+		};
+
+		let action = match trigger {
+			TriggerSource::Software => Action::BLOCK,
+			_ => Action::BEAT,
+		};
+
+		channel.configure(trigger, action, interrupts);
+		unsafe {
+			channel.set_descriptor(descriptor);
+		}
+		// A transfer error left over from the last user would fail this one
+		channel.clear_interrupts();
+
+		// The buffers must be written before the DMAC reads them
+		compiler_fence(Ordering::SeqCst);
+
+		channel.enable();
+		if let TriggerSource::Software = trigger {
+			channel.trigger();
+		}
+
+		Transfer {
+			channel,
+			source,
+			destination,
+		}
+	}
+}
+
+impl<Ch, Src, Dst> Transfer<Ch, Src, Dst>
+where
+	Ch: Channel,
+{
+	/// The DMAC disables the channel once the last beat has been written, or
+	/// if the transfer failed, see `is_failed`.
+	pub fn is_complete(&self) -> bool {
+		!self.channel.is_enabled()
+	}
+
+	/// Whether the DMAC stopped the transfer with an error. This relies on
+	/// the channel's TERR flag, which must not be cleared by an interrupt
+	/// handler while the transfer is owned.
+	pub fn is_failed(&self) -> bool {
+		self.channel.is_failed()
+	}
+
+	/// Block until the transfer is complete, then release the channel and
+	/// buffers. If the DMAC stopped it with an error the destination may have
+	/// been partially written.
+	pub fn wait(self) -> Result<(Ch, Src, Dst), (Error, Ch, Src, Dst)> {
+		while !self.is_complete() {
+			cortex_m::asm::nop();
+		}
+
+		// The DMAC writes must be visible before the buffers are read
+		compiler_fence(Ordering::SeqCst);
+
+		if self.is_failed() {
+			Err((Error::Transfer, self.channel, self.source, self.destination))
+		} else {
+			Ok((self.channel, self.source, self.destination))
+		}
+	}
+
+	/// Abort the transfer, then release the channel and buffers. The
+	/// destination may have been partially written.
+	pub fn stop(mut self) -> (Ch, Src, Dst) {
+		self.channel.disable();
+
+		compiler_fence(Ordering::SeqCst);
+
+		(self.channel, self.source, self.destination)
+	}
+}
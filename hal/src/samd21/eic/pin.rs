@@ -1,5 +1,6 @@
 use crate::{
     target_device,
+    events,
     gpio::{
         self,
         Port,
@@ -16,7 +17,55 @@ pub trait EicPin<T> {
 
 pub type Sense = target_device::eic::config::SENSE0_A;
 
-// TODO implement support for NMI
+pub type NmiSense = target_device::eic::nmictrl::NMISENSE_A;
+
+/// The non-maskable interrupt pin. Its interrupt is taken by the
+/// `NonMaskableInt` exception handler, which runs even while interrupts are
+/// masked.
+pub struct NmiPin<GPIO>(GPIO);
+
+impl<GPIO> NmiPin<GPIO> {
+    /// Construct the NMI from the appropriate pin in any mode.
+    /// You may find it more convenient to use the `into_ei` trait
+    /// and avoid referencing the pin type.
+    pub fn new(pin: GPIO) -> Self {
+        NmiPin(pin)
+    }
+
+    pub fn sense(&mut self, eic: &mut super::EIC, sense: NmiSense) {
+        eic.eic.nmictrl.modify(|_, w| w.nmisense().variant(sense));
+    }
+
+    pub fn filter(&mut self, eic: &mut super::EIC, filter: bool) {
+        eic.eic.nmictrl.modify(|_, w| w.nmifilten().bit(filter));
+    }
+
+    pub fn is_interrupt(&mut self) -> bool {
+        nmi_is_interrupt()
+    }
+
+    pub fn clear_interrupt(&mut self) {
+        nmi_clear_interrupt()
+    }
+}
+
+/// Whether the NMI pin has detected its sense condition. The `NmiPin` can't
+/// be shared safely with the `NonMaskableInt` handler, so this is available
+/// without it.
+pub fn nmi_is_interrupt() -> bool {
+    unsafe { &(*target_device::EIC::ptr()) }.nmiflag.read().nmi().bit_is_set()
+}
+
+/// Clear the NMI flag, call this from the `NonMaskableInt` handler.
+pub fn nmi_clear_interrupt() {
+    unsafe { &(*target_device::EIC::ptr()) }.nmiflag.write(|w| w.nmi().set_bit());
+}
+
+impl<MODE> EicPin<NmiPin<gpio::Pa8<gpio::PfA>>> for gpio::Pa8<MODE> {
+    fn into_ei(self, port: &mut Port) -> NmiPin<gpio::Pa8<gpio::PfA>> {
+        NmiPin::new(self.into_function(port))
+    }
+}
 
 /// The pad macro defines the given EIC pin and implements EicPin for the
 /// given pins. The EicPin implementation will configure the pin for the
@@ -49,8 +98,15 @@ crate::paste::item! {
             });
         }
 
+        /// Enable the event output for this external interrupt, returning the
+        /// generator to publish on an event channel.
+        pub fn event(&mut self, eic: &mut super::EIC) -> events::GeneratorHandle<target_device::EIC> {
+            self.enable_event(eic);
+            events::GeneratorHandle::new(events::Generator::[<EXTINT $num>])
+        }
+
         pub fn enable_interrupt(&mut self, eic: &mut super::EIC) {
-            eic.eic.intenset.modify(|_, w| {
+            eic.eic.intenset.write(|w| {
                 w.[<extint $num>]().set_bit()
             });
         }
@@ -62,7 +118,7 @@ crate::paste::item! {
         }
 
         pub fn disable_interrupt(&mut self, eic: &mut super::EIC) {
-            eic.eic.intenclr.modify(|_, w| {
+            eic.eic.intenclr.write(|w| {
                 w.[<extint $num>]().set_bit()
             });
         }
@@ -72,7 +128,7 @@ crate::paste::item! {
         }
 
         pub fn clear_interrupt(&mut self) {
-            unsafe { &(*target_device::EIC::ptr()) }.intflag.modify(|_, w| {
+            unsafe { &(*target_device::EIC::ptr()) }.intflag.write(|w| {
                 w.[<extint $num>]().set_bit()
             });
         }
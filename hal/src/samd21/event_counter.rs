@@ -1,14 +1,13 @@
-use core::ops::Deref;
-
 use crate::{
 	target_device,
 	clock,
+	events,
+	timer::{Count8, Count16, Count32},
 };
 
-pub trait Count16 {
-    fn count16(&self) -> &target_device::tc3::COUNT16;
-}
-
+/// A TC counting the events on its input in 16-bit mode, see
+/// `EventCounter8` for 8-bit mode and `EventCounter32` for pairs of counters
+/// in 32-bit mode.
 pub struct EventCounter<TC> {
 	tc: TC,
 }
@@ -16,47 +15,118 @@ pub struct EventCounter<TC> {
 impl<TC> EventCounter<TC>
 where TC: Count16 {
 	pub fn enable_overflow_interrupt(&mut self) {
-		self.tc.count16().intenset.modify(|_, w| w.ovf().set_bit());
+		self.tc.count_16().intenset.modify(|_, w| w.ovf().set_bit());
 	}
 
 	pub fn diable_overflow_interrupt(&mut self) {
-		self.tc.count16().intenclr.modify(|_, w| w.ovf().set_bit());
+		self.tc.count_16().intenclr.modify(|_, w| w.ovf().set_bit());
 	}
 
 	pub fn clear_overflow_interrupt(&mut self) {
-        self.tc.count16().intflag.modify(|_, w| w.ovf().set_bit());
+        self.tc.count_16().intflag.modify(|_, w| w.ovf().set_bit());
     }
 
     pub fn enable_overflow_event(&mut self) {
-        self.tc.count16().evctrl.modify(|_, w| w.ovfeo().set_bit());
+        self.tc.count_16().evctrl.modify(|_, w| w.ovfeo().set_bit());
     }
 
 	pub fn count(&self) -> u16 {
-		self.tc.count16().count.read().bits()
+		self.tc.count_16().count.read().bits()
 	}
 
 	pub fn clear(&mut self) {
-		self.tc.count16().count.reset()
+		self.tc.count_16().count.reset()
 	}
 }
 
-macro_rules! ec {
-    ($($TYPE:ident: ($TC:ident, $pm:ident, $clock:ident),)+) => {
-        $(
-impl Count16 for target_device::$TC {
-	fn count16(&self) -> &target_device::tc3::COUNT16 {
-        let register: &target_device::tc3::RegisterBlock = self.deref();
-        register.count16()
+/// A TC counting the events on its input in 8-bit mode.
+pub struct EventCounter8<TC> {
+    tc: TC,
+}
+
+impl<TC> EventCounter8<TC>
+where TC: Count8 {
+    pub fn enable_overflow_interrupt(&mut self) {
+        self.tc.count_8().intenset.write(|w| w.ovf().set_bit());
+    }
+
+    pub fn disable_overflow_interrupt(&mut self) {
+        self.tc.count_8().intenclr.write(|w| w.ovf().set_bit());
+    }
+
+    pub fn clear_overflow_interrupt(&mut self) {
+        self.tc.count_8().intflag.write(|w| w.ovf().set_bit());
+    }
+
+    pub fn enable_overflow_event(&mut self) {
+        self.tc.count_8().evctrl.modify(|_, w| w.ovfeo().set_bit());
+    }
+
+    pub fn count(&self) -> u8 {
+        self.tc.count_8().count.read().bits()
+    }
+
+    pub fn clear(&mut self) {
+        self.tc.count_8().count.reset()
+    }
+}
+
+/// A pair of TCs counting the events on the master's input in 32-bit mode.
+/// The slave is held so it can't be used while the pair is counting.
+pub struct EventCounter32<Master, Slave> {
+    tc: Master,
+    slave: Slave,
+}
+
+impl<Master, Slave> EventCounter32<Master, Slave>
+where Master: Count32 {
+    pub fn enable_overflow_interrupt(&mut self) {
+        self.tc.count_32().intenset.write(|w| w.ovf().set_bit());
+    }
+
+    pub fn disable_overflow_interrupt(&mut self) {
+        self.tc.count_32().intenclr.write(|w| w.ovf().set_bit());
+    }
+
+    pub fn clear_overflow_interrupt(&mut self) {
+        self.tc.count_32().intflag.write(|w| w.ovf().set_bit());
+    }
+
+    pub fn enable_overflow_event(&mut self) {
+        self.tc.count_32().evctrl.modify(|_, w| w.ovfeo().set_bit());
+    }
+
+    pub fn count(&self) -> u32 {
+        self.tc.count_32().count.read().bits()
+    }
+
+    pub fn clear(&mut self) {
+        self.tc.count_32().count.reset()
+    }
+
+    /// Disable the pair and release the two counters.
+    pub fn free(self) -> (Master, Slave) {
+        let counter = self.tc.count_32();
+        counter.ctrla.modify(|_, w| w.enable().clear_bit());
+        while counter.status.read().syncbusy().bit_is_set() {
+            cortex_m::asm::nop();
+        }
+
+        (self.tc, self.slave)
     }
 }
 
+macro_rules! ec {
+    ($($TYPE:ident: ($TC:ident, $pm:ident, $clock:ident, $user:ident, $overflow:ident),)+) => {
+        $(
+pub type $TYPE = EventCounter<target_device::$TC>;
+
 impl EventCounter<target_device::$TC>
-where target_device::$TC: Count16
 {
     pub fn $pm(_clock: &clock::$clock, tc: target_device::$TC, max: u16, pm: &mut target_device::PM) -> Self {
         pm.apbcmask.modify(|_, w| w.$pm().set_bit());
 
-	    let counter = tc.count16();
+	    let counter = tc.count_16();
 	    counter.evctrl.modify(|_, w| {
 	        w.tcei().set_bit();
 	        w.evact().count()
@@ -83,13 +153,152 @@ where target_device::$TC: Count16
 			tc,
 	    }
     }
+
+    /// Enable the overflow event output, returning the generator to publish
+    /// on an event channel.
+    pub fn overflow_event(&mut self) -> events::GeneratorHandle<target_device::$TC> {
+        self.enable_overflow_event();
+        events::GeneratorHandle::new(events::Generator::$overflow)
+    }
+}
+
+/// The counter's event input, subscribe it to the channel whose events should
+/// be counted
+impl events::EventUser for EventCounter<target_device::$TC> {
+    fn event_user(&self) -> events::User {
+        events::User::$user
+    }
+}
+
+impl EventCounter8<target_device::$TC>
+{
+    /// Configure the TC to count events in 8-bit mode, wrapping after `max`.
+    pub fn $pm(_clock: &clock::$clock, tc: target_device::$TC, max: u8, pm: &mut target_device::PM) -> Self {
+        pm.apbcmask.modify(|_, w| w.$pm().set_bit());
+
+        let counter = tc.count_8();
+        // The mode is enable protected and sets the size of the other
+        // registers, so it goes first
+        counter.ctrla.modify(|_, w| w.mode().count8());
+        counter.evctrl.modify(|_, w| {
+            w.tcei().set_bit();
+            w.evact().count()
+        });
+
+        counter.cc[0].write(|w| unsafe {
+            w.cc().bits(max)
+        });
+        while counter.status.read().syncbusy().bit_is_set() {
+            cortex_m::asm::nop();
+        }
+
+        counter.ctrla.modify(|_, w| {
+            w.runstdby().set_bit();
+            // Use cc[0] as TOP
+            w.wavegen().mfrq();
+            w.enable().set_bit()
+        });
+        while counter.status.read().syncbusy().bit_is_set() {
+            cortex_m::asm::nop();
+        }
+
+        Self {
+            tc,
+        }
+    }
+
+    /// Enable the overflow event output, returning the generator to publish
+    /// on an event channel.
+    pub fn overflow_event(&mut self) -> events::GeneratorHandle<target_device::$TC> {
+        self.enable_overflow_event();
+        events::GeneratorHandle::new(events::Generator::$overflow)
+    }
+}
+
+/// The counter's event input, subscribe it to the channel whose events should
+/// be counted
+impl events::EventUser for EventCounter8<target_device::$TC> {
+    fn event_user(&self) -> events::User {
+        events::User::$user
+    }
+}
+        )+
+    }
+}
+
+macro_rules! ec32 {
+    ($($TYPE:ident: ($MASTER:ident, $SLAVE:ident, $pm:ident, $master_pm:ident, $slave_pm:ident, $clock:ident, $user:ident, $overflow:ident),)+) => {
+        $(
+pub type $TYPE = EventCounter32<target_device::$MASTER, target_device::$SLAVE>;
+
+impl EventCounter32<target_device::$MASTER, target_device::$SLAVE>
+{
+    /// Configure the pair to count the events on the master's input in
+    /// 32-bit mode, wrapping after `max`.
+    pub fn $pm(_clock: &clock::$clock, master: target_device::$MASTER, slave: target_device::$SLAVE, max: u32, pm: &mut target_device::PM) -> Self {
+        // Both counters of the pair need their bus clock
+        pm.apbcmask.modify(|_, w| {
+            w.$master_pm().set_bit();
+            w.$slave_pm().set_bit()
+        });
+
+        let counter = master.count_32();
+        // The mode is enable protected and sets the size of the other
+        // registers, so it goes first. The slave follows the master.
+        counter.ctrla.modify(|_, w| w.mode().count32());
+        counter.evctrl.modify(|_, w| {
+            w.tcei().set_bit();
+            w.evact().count()
+        });
+
+        counter.cc[0].write(|w| unsafe {
+            w.cc().bits(max)
+        });
+        while counter.status.read().syncbusy().bit_is_set() {
+            cortex_m::asm::nop();
+        }
+
+        counter.ctrla.modify(|_, w| {
+            w.runstdby().set_bit();
+            // Use cc[0] as TOP
+            w.wavegen().mfrq();
+            w.enable().set_bit()
+        });
+        while counter.status.read().syncbusy().bit_is_set() {
+            cortex_m::asm::nop();
+        }
+
+        Self {
+            tc: master,
+            slave,
+        }
+    }
+
+    /// Enable the overflow event output, returning the generator to publish
+    /// on an event channel.
+    pub fn overflow_event(&mut self) -> events::GeneratorHandle<target_device::$MASTER> {
+        self.enable_overflow_event();
+        events::GeneratorHandle::new(events::Generator::$overflow)
+    }
+}
+
+/// The master's event input, subscribe it to the channel whose events should
+/// be counted
+impl events::EventUser for EventCounter32<target_device::$MASTER, target_device::$SLAVE> {
+    fn event_user(&self) -> events::User {
+        events::User::$user
+    }
 }
         )+
     }
 }
 
 ec! {
-    EventCounter3: (TC3, tc3_, Tcc2Tc3Clock),
-    EventCounter4: (TC4, tc4_, Tc4Tc5Clock),
-    EventCounter5: (TC5, tc5_, Tc4Tc5Clock),
+    EventCounter3: (TC3, tc3_, Tcc2Tc3Clock, TC3, TC3_OVF),
+    EventCounter4: (TC4, tc4_, Tc4Tc5Clock, TC4, TC4_OVF),
+    EventCounter5: (TC5, tc5_, Tc4Tc5Clock, TC5, TC5_OVF),
+}
+
+ec32! {
+    EventCounter4_5: (TC4, TC5, tc4_tc5, tc4_, tc5_, Tc4Tc5Clock, TC4, TC4_OVF),
 }
@@ -3,8 +3,9 @@
 #![allow(non_snake_case)]
 #![allow(non_camel_case_types)]
 
+use core::marker::PhantomData;
+
 use crate::{
-	clock,
 	target_device::{
 		self,
 		evsys,
@@ -31,12 +32,30 @@ pub enum Channel {
 	Channel11 = 11,
 }
 
+/// The event users, as selected by `USER.USER`
 #[derive(Clone, Copy)]
 pub enum User {
-	DMAC_CH0 = 0x0,
-	DMAC_CH1 = 0x1,
-	DMAC_CH2 = 0x2,
-	DMAC_CH3 = 0x3,
+	DMAC_CH0 = 0x00,
+	DMAC_CH1 = 0x01,
+	DMAC_CH2 = 0x02,
+	DMAC_CH3 = 0x03,
+
+	TCC0_EV0 = 0x04,
+	TCC0_EV1 = 0x05,
+	TCC0_MC0 = 0x06,
+	TCC0_MC1 = 0x07,
+	TCC0_MC2 = 0x08,
+	TCC0_MC3 = 0x09,
+
+	TCC1_EV0 = 0x0A,
+	TCC1_EV1 = 0x0B,
+	TCC1_MC0 = 0x0C,
+	TCC1_MC1 = 0x0D,
+
+	TCC2_EV0 = 0x0E,
+	TCC2_EV1 = 0x0F,
+	TCC2_MC0 = 0x10,
+	TCC2_MC1 = 0x11,
 
 	TC3 = 0x12,
 	TC4 = 0x13,
@@ -45,24 +64,75 @@ pub enum User {
 	TC7 = 0x16,
 
 	ADC_START = 0x17,
+	ADC_SYNC = 0x18,
+
+	AC_SOC0 = 0x19,
+	AC_SOC1 = 0x1A,
+
+	DAC_START = 0x1B,
+
+	PTC_STCONV = 0x1C,
 }
 
+/// The event generators, as selected by `CHANNEL.EVGEN`
 #[derive(Clone, Copy)]
 pub enum Generator {
-	NONE = 0x0,
-	RTC_PER0 = 0x4,
-	RTC_PER1 = 0x5,
-	RTC_PER2 = 0x6,
-	RTC_PER3 = 0x7,
-	RTC_PER4 = 0x8,
-	RTC_PER5 = 0x9,
-	RTC_PER6 = 0xA,
-	RTC_PER7 = 0xB,
-	EXTINT2 = 0xE,
+	NONE = 0x00,
+
+	RTC_CMP0 = 0x01,
+	RTC_CMP1 = 0x02,
+	RTC_OVF = 0x03,
+	RTC_PER0 = 0x04,
+	RTC_PER1 = 0x05,
+	RTC_PER2 = 0x06,
+	RTC_PER3 = 0x07,
+	RTC_PER4 = 0x08,
+	RTC_PER5 = 0x09,
+	RTC_PER6 = 0x0A,
+	RTC_PER7 = 0x0B,
+
+	EXTINT0 = 0x0C,
+	EXTINT1 = 0x0D,
+	EXTINT2 = 0x0E,
+	EXTINT3 = 0x0F,
+	EXTINT4 = 0x10,
+	EXTINT5 = 0x11,
+	EXTINT6 = 0x12,
+	EXTINT7 = 0x13,
+	EXTINT8 = 0x14,
+	EXTINT9 = 0x15,
+	EXTINT10 = 0x16,
+	EXTINT11 = 0x17,
+	EXTINT12 = 0x18,
+	EXTINT13 = 0x19,
+	EXTINT14 = 0x1A,
+	EXTINT15 = 0x1B,
+
 	DMAC_CH0 = 0x1E,
 	DMAC_CH1 = 0x1F,
 	DMAC_CH2 = 0x20,
 	DMAC_CH3 = 0x21,
+
+	TCC0_OVF = 0x22,
+	TCC0_TRG = 0x23,
+	TCC0_CNT = 0x24,
+	TCC0_MC0 = 0x25,
+	TCC0_MC1 = 0x26,
+	TCC0_MC2 = 0x27,
+	TCC0_MC3 = 0x28,
+
+	TCC1_OVF = 0x29,
+	TCC1_TRG = 0x2A,
+	TCC1_CNT = 0x2B,
+	TCC1_MC0 = 0x2C,
+	TCC1_MC1 = 0x2D,
+
+	TCC2_OVF = 0x2E,
+	TCC2_TRG = 0x2F,
+	TCC2_CNT = 0x30,
+	TCC2_MC0 = 0x31,
+	TCC2_MC1 = 0x32,
+
 	TC3_OVF = 0x33,
 	TC3_MC0 = 0x34,
 	TC3_MC1 = 0x35,
@@ -72,6 +142,24 @@ pub enum Generator {
 	TC5_OVF = 0x39,
 	TC5_MC0 = 0x3A,
 	TC5_MC1 = 0x3B,
+	TC6_OVF = 0x3C,
+	TC6_MC0 = 0x3D,
+	TC6_MC1 = 0x3E,
+	TC7_OVF = 0x3F,
+	TC7_MC0 = 0x40,
+	TC7_MC1 = 0x41,
+
+	ADC_RESRDY = 0x42,
+	ADC_WINMON = 0x43,
+
+	AC_COMP0 = 0x44,
+	AC_COMP1 = 0x45,
+	AC_WIN0 = 0x46,
+
+	DAC_EMPTY = 0x47,
+
+	PTC_EOC = 0x48,
+	PTC_WCOMP = 0x49,
 }
 
 pub enum EdgeSelector {
@@ -90,38 +178,70 @@ impl Into<evsys::channel::EDGSEL_A> for EdgeSelector {
 	}
 }
 
-pub trait Clock {
+/// The path an event takes through its channel. The synchronous and
+/// resynchronized paths need the generic clock of the channel, for example
+/// `GenericClockController::evch0` for `Channel0`.
+pub enum Path {
+	Asynchronous,
+	Synchronous(EdgeSelector),
+	Resynchronized(EdgeSelector),
+}
+
+/// Something that can be published on an event channel. Peripheral drivers
+/// hand these out once they have enabled the matching event output, for
+/// example `TimerCounter::overflow_event`.
+pub trait EventGenerator {
+	fn event_generator(&self) -> Generator;
+}
+
+/// Something that can be subscribed to an event channel. Peripheral drivers
+/// hand these out once they have enabled the matching event input.
+pub trait EventUser {
+	fn event_user(&self) -> User;
+}
 
+/// An event output that the driver of peripheral `P` has enabled, handed
+/// out by methods like `TimerCounter::overflow_event`.
+pub struct GeneratorHandle<P> {
+	generator: Generator,
+	_peripheral: PhantomData<P>,
 }
 
-macro_rules! ev_clock {
-	($($Clock:ident,)+) => (
-		$(
-			impl Clock for clock::$Clock {}
-		)+
-	)
+impl<P> GeneratorHandle<P> {
+	pub(crate) fn new(generator: Generator) -> Self {
+		Self {
+			generator,
+			_peripheral: PhantomData,
+		}
+	}
 }
 
-ev_clock! {
-	Evch0Clock,
-	Evch1Clock,
-	Evch2Clock,
-	Evch3Clock,
-	Evch4Clock,
-	Evch5Clock,
-	Evch6Clock,
-	Evch7Clock,
-	Evch8Clock,
-	Evch9Clock,
-	Evch10Clock,
-	Evch11Clock,
+impl<P> EventGenerator for GeneratorHandle<P> {
+	fn event_generator(&self) -> Generator {
+		self.generator
+	}
 }
 
-// TODO enforce that the correct clock is passed for each event
-pub enum Path<'a> {
-	Asynchronous,
-	Synchronous(&'a dyn Clock, EdgeSelector),
-	Resynchronized(&'a dyn Clock, EdgeSelector),
+/// An event input that the driver of peripheral `P` has enabled, handed out
+/// by methods like `Adc::start_conversion_event`.
+pub struct UserHandle<P> {
+	user: User,
+	_peripheral: PhantomData<P>,
+}
+
+impl<P> UserHandle<P> {
+	pub(crate) fn new(user: User) -> Self {
+		Self {
+			user,
+			_peripheral: PhantomData,
+		}
+	}
+}
+
+impl<P> EventUser for UserHandle<P> {
+	fn event_user(&self) -> User {
+		self.user
+	}
 }
 
 impl EventSys {
@@ -133,19 +253,20 @@ impl EventSys {
 		}
 	}
 
-	pub fn connect(&mut self, channel: Channel, path: Path, generator: Generator, user: User) {
+	/// Route `generator` to `user` through `channel`, taking `path`.
+	pub fn connect<G: EventGenerator, U: EventUser>(&mut self, channel: Channel, path: Path, generator: &G, user: &U) {
 		self.subscribe(channel, user);
 		self.publish(channel, path, generator);
 	}
 
-	pub fn subscribe(&mut self, channel: Channel, user: User) {
+	pub fn subscribe<U: EventUser>(&mut self, channel: Channel, user: &U) {
 		self.evsys.user.write(|w| unsafe {
 			w.channel().bits(channel as u8 + 1);
-			w.user().bits(user as u8)
+			w.user().bits(user.event_user() as u8)
 		});
 	}
 
-	pub fn publish(&mut self, channel: Channel, path: Path, generator: Generator) {
+	pub fn publish<G: EventGenerator>(&mut self, channel: Channel, path: Path, generator: &G) {
 		self.evsys.channel.write(|w| unsafe {
 			w.channel().bits(channel as u8);
 
@@ -153,18 +274,18 @@ impl EventSys {
 				Path::Asynchronous => {
 					w.path().asynchronous();
 				},
-				Path::Synchronous(_, edge_sel) => {
+				Path::Synchronous(edge_sel) => {
 					w.path().synchronous();
 					w.edgsel().variant(edge_sel.into());
 				},
-				Path::Resynchronized(_, edge_sel) => {
+				Path::Resynchronized(edge_sel) => {
 					w.path().resynchronized();
 					w.edgsel().variant(edge_sel.into());
 				},
 			}
 
-			w.evgen().bits(generator as u8)
-    	});
+			w.evgen().bits(generator.event_generator() as u8)
+		});
 	}
 
 	pub fn enable_event_detected_interrupt(&mut self, channel: Channel) {
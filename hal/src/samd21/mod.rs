@@ -12,6 +12,8 @@ mod serial_number;
 pub use serial_number::*;
 
 pub mod adc;
+pub mod dac;
+pub mod ac;
 
 #[cfg(feature = "usb")]
 pub mod usb;
@@ -25,7 +27,9 @@ pub mod nvic;
 // Timers
 pub mod timer;
 pub mod event_counter;
+pub mod capture;
 pub mod timeout;
+pub mod one_pulse;
 
 /// ResetCause represents the reason the MCU was reset.
 #[derive(Debug, Clone, Copy)]
@@ -0,0 +1,379 @@
+//! One-pulse outputs on the TC and TCC peripherals.
+//!
+//! Each trigger, from software or the counter's event input, runs the counter
+//! once from zero. The output waits for the delay, is active for the width,
+//! then goes back to its idle level until the next trigger. A trigger while
+//! the pulse is running restarts it, so the output works as a retriggerable
+//! monostable.
+//!
+//! The output is WO[1] of a TC, or the WO[n] of the chosen TCC channel, and
+//! the pin must be switched to the counter's peripheral function.
+//!
+//! ```ignore
+//! let mut strobe = OnePulse::<TC4>::tc4_(&tc4_clock, peripherals.TC4, Prescaler::DIV1, Polarity::ActiveHigh, &mut pm);
+//! strobe.set_timing(strobe.ticks(10), strobe.ticks(100));
+//!
+//! let generator = pin.event(&mut eic);
+//! event_sys.connect(Channel::Channel0, Path::Asynchronous, &generator, &strobe);
+//! ```
+use crate::{
+    target_device,
+    clock,
+    events,
+    time::Hertz,
+    timer::Count16,
+};
+
+use core::ops::Deref;
+
+/// The division of the TC clock that the counter ticks at
+pub type Prescaler = target_device::tc3::count16::ctrla::PRESCALER_A;
+
+/// The division of the TCC clock that the counter ticks at
+pub type TccPrescaler = target_device::tcc0::ctrla::PRESCALER_A;
+
+/// The level of the output during the pulse, it idles at the other level
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Polarity {
+    ActiveHigh,
+    ActiveLow,
+}
+
+/// The compare channel of a TCC, the pulse is output on the WO with the same
+/// index
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Channel {
+    _0,
+    _1,
+    _2,
+    _3,
+}
+
+fn divider(prescaler: u8) -> u32 {
+    match prescaler {
+        0 => 1,
+        1 => 2,
+        2 => 4,
+        3 => 8,
+        4 => 16,
+        5 => 64,
+        6 => 256,
+        _ => 1024,
+    }
+}
+
+// The output is cleared at the compare match and set again when the counter
+// wraps, so it is inverted to make the pulse run from the match to the top
+fn inverted(polarity: Polarity) -> bool {
+    polarity == Polarity::ActiveHigh
+}
+
+/// A TC generating one pulse per trigger on WO[1].
+pub struct OnePulse<TC> {
+    tc: TC,
+    freq: Hertz,
+}
+
+impl<TC> OnePulse<TC>
+where
+    TC: Count16,
+{
+    /// The frequency the counter ticks at, the unit of the delay and width.
+    pub fn frequency(&self) -> Hertz {
+        self.freq
+    }
+
+    /// The number of ticks closest to `micros` microseconds.
+    pub fn ticks(&self, micros: u32) -> u32 {
+        ((micros as u64 * self.freq.0 as u64 + 500_000) / 1_000_000) as u32
+    }
+
+    /// Set the time from the trigger to the start of the pulse, and the
+    /// length of the pulse, in ticks. The delay must be at least one tick.
+    ///
+    /// Panics if the pulse ends more than 65535 ticks after the trigger.
+    pub fn set_timing(&mut self, delay: u32, width: u32) {
+        let delay = delay.max(1);
+        // An overflow is past any top, so it fails the assert below
+        let top = delay.checked_add(width).unwrap_or(u32::max_value());
+        assert!(top <= u16::max_value() as u32, "pulse ends more than 65535 ticks after the trigger");
+
+        let count = self.tc.count_16();
+        count.cc[0].write(|w| unsafe { w.cc().bits(top as u16) });
+        while count.status.read().syncbusy().bit_is_set() {}
+        count.cc[1].write(|w| unsafe { w.cc().bits(delay as u16) });
+        while count.status.read().syncbusy().bit_is_set() {}
+    }
+
+    /// Start the pulse from software, restarting it if it is running.
+    pub fn trigger(&mut self) {
+        let count = self.tc.count_16();
+        count.ctrlbset.write(|w| w.cmd().retrigger());
+        while count.status.read().syncbusy().bit_is_set() {}
+    }
+
+    /// Whether a pulse is being timed.
+    pub fn is_running(&self) -> bool {
+        self.tc.count_16().status.read().stop().bit_is_clear()
+    }
+
+    /// Interrupt at the end of each pulse.
+    pub fn enable_interrupt(&mut self) {
+        self.tc.count_16().intenset.write(|w| w.ovf().set_bit());
+    }
+
+    pub fn disable_interrupt(&mut self) {
+        self.tc.count_16().intenclr.write(|w| w.ovf().set_bit());
+    }
+
+    /// Whether a pulse has ended since the flag was cleared.
+    pub fn is_complete(&self) -> bool {
+        self.tc.count_16().intflag.read().ovf().bit_is_set()
+    }
+
+    pub fn clear_complete(&mut self) {
+        self.tc.count_16().intflag.write(|w| w.ovf().set_bit());
+    }
+}
+
+/// A TCC generating one pulse per trigger on the WO of its channel. All the
+/// channels share the counter, so each can give a pulse with its own delay
+/// that ends with the others.
+pub struct TccOnePulse<TCC> {
+    tcc: TCC,
+    freq: Hertz,
+    // The top of the counter, 24 bits on TCC0 and TCC1 and 16 bits on TCC2
+    max: u32,
+}
+
+impl<TCC> TccOnePulse<TCC>
+where
+    TCC: Deref<Target = target_device::tcc0::RegisterBlock>,
+{
+    /// The frequency the counter ticks at, the unit of the delay and width.
+    pub fn frequency(&self) -> Hertz {
+        self.freq
+    }
+
+    /// The number of ticks closest to `micros` microseconds.
+    pub fn ticks(&self, micros: u32) -> u32 {
+        ((micros as u64 * self.freq.0 as u64 + 500_000) / 1_000_000) as u32
+    }
+
+    /// Set the time from the trigger to the start of the pulse on `channel`,
+    /// and the length of the pulse, in ticks. The delay must be at least one
+    /// tick. This sets the end of the pulses on every channel.
+    ///
+    /// Panics if the pulse ends more than 2^24 - 1 ticks after the trigger,
+    /// or 65535 on TCC2.
+    pub fn set_timing(&mut self, channel: Channel, delay: u32, width: u32) {
+        let delay = delay.max(1);
+        // An overflow is past any top, so it fails the assert below
+        let top = delay.checked_add(width).unwrap_or(u32::max_value());
+        assert!(top <= self.max, "pulse ends after the top of the counter");
+
+        self.tcc.per().write(|w| unsafe { w.per().bits(top) });
+        while self.tcc.syncbusy.read().per().bit_is_set() {}
+        self.tcc.cc()[channel as usize].write(|w| unsafe { w.cc().bits(delay) });
+        while self.tcc.syncbusy.read().bits() != 0 {}
+    }
+
+    /// Start the pulse from software, restarting it if it is running.
+    pub fn trigger(&mut self) {
+        self.tcc.ctrlbset.write(|w| w.cmd().retrigger());
+        while self.tcc.syncbusy.read().ctrlb().bit_is_set() {}
+    }
+
+    /// Whether a pulse is being timed.
+    pub fn is_running(&self) -> bool {
+        self.tcc.status.read().stop().bit_is_clear()
+    }
+
+    /// Interrupt at the end of each pulse.
+    pub fn enable_interrupt(&mut self) {
+        self.tcc.intenset.write(|w| w.ovf().set_bit());
+    }
+
+    pub fn disable_interrupt(&mut self) {
+        self.tcc.intenclr.write(|w| w.ovf().set_bit());
+    }
+
+    /// Whether a pulse has ended since the flag was cleared.
+    pub fn is_complete(&self) -> bool {
+        self.tcc.intflag.read().ovf().bit_is_set()
+    }
+
+    pub fn clear_complete(&mut self) {
+        self.tcc.intflag.write(|w| w.ovf().set_bit());
+    }
+}
+
+macro_rules! one_pulse {
+    ($($TYPE:ident: ($TC:ident, $pm:ident, $clock:ident, $user:ident),)+) => {
+        $(
+pub type $TYPE = OnePulse<target_device::$TC>;
+
+impl OnePulse<target_device::$TC> {
+    /// Configure the TC to output one pulse per trigger, the pulse timing
+    /// must be set with `set_timing` before the first trigger.
+    pub fn $pm(clock: &clock::$clock, tc: target_device::$TC, prescaler: Prescaler, polarity: Polarity, pm: &mut target_device::PM) -> Self {
+        pm.apbcmask.modify(|_, w| w.$pm().set_bit());
+
+        let count = tc.count_16();
+
+        count.ctrla.modify(|_, w| w.enable().clear_bit());
+        while count.status.read().syncbusy().bit_is_set() {}
+        count.ctrla.write(|w| w.swrst().set_bit());
+        while count.status.read().syncbusy().bit_is_set() {}
+        // the SVD erroneously marks swrst as write-only, so we
+        // need to manually read the bit here
+        while count.ctrla.read().bits() & 1 != 0 {}
+
+        count.evctrl.write(|w| {
+            w.tcei().set_bit();
+            w.evact().retrigger()
+        });
+
+        count.ctrlc.write(|w| w.inven1().bit(inverted(polarity)));
+        while count.status.read().syncbusy().bit_is_set() {}
+
+        count.ctrlbset.write(|w| {
+            // Count up when the direction bit is zero
+            w.dir().clear_bit();
+            // Stop at the top rather than looping back
+            w.oneshot().set_bit()
+        });
+        while count.status.read().syncbusy().bit_is_set() {}
+
+        // Start at the top so enabling the counter just wraps it to idle
+        count.cc[0].write(|w| unsafe { w.cc().bits(u16::max_value()) });
+        while count.status.read().syncbusy().bit_is_set() {}
+        count.count.write(|w| unsafe { w.count().bits(u16::max_value()) });
+        while count.status.read().syncbusy().bit_is_set() {}
+
+        count.ctrla.write(|w| {
+            w.prescaler().variant(prescaler);
+            // CC0 is the top, WO[1] toggles at the CC1 match
+            w.wavegen().mpwm();
+            w.enable().set_bit()
+        });
+        while count.status.read().syncbusy().bit_is_set() {}
+        // Wait out the wrap from the top so it isn't seen as a pulse
+        while count.status.read().stop().bit_is_clear() {}
+        count.intflag.write(|w| w.ovf().set_bit());
+
+        Self {
+            tc,
+            freq: Hertz(clock.freq().0 / divider(prescaler.into())),
+        }
+    }
+
+    /// Stop the counter and release the TC.
+    pub fn free(self) -> target_device::$TC {
+        let count = self.tc.count_16();
+        count.ctrla.modify(|_, w| w.enable().clear_bit());
+        while count.status.read().syncbusy().bit_is_set() {}
+
+        self.tc
+    }
+}
+
+/// The one-pulse's event input, subscribe it to the channel whose events
+/// should start the pulse
+impl events::EventUser for OnePulse<target_device::$TC> {
+    fn event_user(&self) -> events::User {
+        events::User::$user
+    }
+}
+        )+
+    }
+}
+
+macro_rules! tcc_one_pulse {
+    ($($TYPE:ident: ($TCC:ident, $pm:ident, $clock:ident, $user:ident, $max:expr),)+) => {
+        $(
+pub type $TYPE = TccOnePulse<target_device::$TCC>;
+
+impl TccOnePulse<target_device::$TCC> {
+    /// Configure the TCC to output one pulse per trigger on each channel,
+    /// the pulse timing must be set with `set_timing` before the first
+    /// trigger.
+    pub fn $pm(clock: &clock::$clock, tcc: target_device::$TCC, prescaler: TccPrescaler, polarity: Polarity, pm: &mut target_device::PM) -> Self {
+        pm.apbcmask.modify(|_, w| w.$pm().set_bit());
+
+        tcc.ctrla.modify(|_, w| w.enable().clear_bit());
+        while tcc.syncbusy.read().enable().bit_is_set() {}
+        tcc.ctrla.write(|w| w.swrst().set_bit());
+        while tcc.syncbusy.read().swrst().bit_is_set() {}
+
+        // The event action and output inversion are enable protected
+        tcc.evctrl.write(|w| {
+            w.tcei0().set_bit();
+            w.evact0().retrigger()
+        });
+        tcc.drvctrl.write(|w| unsafe {
+            // INVENn are bits 16 to 23
+            w.bits(if inverted(polarity) { 0xff << 16 } else { 0 })
+        });
+
+        tcc.ctrlbset.write(|w| {
+            // Stop at the top rather than looping back
+            w.oneshot().set_bit()
+        });
+        while tcc.syncbusy.read().ctrlb().bit_is_set() {}
+
+        tcc.wave.write(|w| w.wavegen().npwm());
+        while tcc.syncbusy.read().wave().bit_is_set() {}
+
+        // Start at the top so enabling the counter just wraps it to idle
+        let top = tcc.per().read().per().bits();
+        tcc.count().write(|w| unsafe { w.count().bits(top) });
+        while tcc.syncbusy.read().count().bit_is_set() {}
+
+        tcc.ctrla.write(|w| {
+            w.prescaler().variant(prescaler);
+            w.enable().set_bit()
+        });
+        while tcc.syncbusy.read().enable().bit_is_set() {}
+        // Wait out the wrap from the top so it isn't seen as a pulse
+        while tcc.status.read().stop().bit_is_clear() {}
+        tcc.intflag.write(|w| w.ovf().set_bit());
+
+        Self {
+            tcc,
+            freq: Hertz(clock.freq().0 / divider(prescaler.into())),
+            max: $max,
+        }
+    }
+
+    /// Stop the counter and release the TCC.
+    pub fn free(self) -> target_device::$TCC {
+        self.tcc.ctrla.modify(|_, w| w.enable().clear_bit());
+        while self.tcc.syncbusy.read().enable().bit_is_set() {}
+
+        self.tcc
+    }
+}
+
+/// The one-pulse's event input, subscribe it to the channel whose events
+/// should start the pulse
+impl events::EventUser for TccOnePulse<target_device::$TCC> {
+    fn event_user(&self) -> events::User {
+        events::User::$user
+    }
+}
+        )+
+    }
+}
+
+one_pulse! {
+    OnePulse3: (TC3, tc3_, Tcc2Tc3Clock, TC3),
+    OnePulse4: (TC4, tc4_, Tc4Tc5Clock, TC4),
+    OnePulse5: (TC5, tc5_, Tc4Tc5Clock, TC5),
+}
+
+tcc_one_pulse! {
+    TccOnePulse0: (TCC0, tcc0_, Tcc0Tcc1Clock, TCC0_EV0, (1 << 24) - 1),
+    TccOnePulse1: (TCC1, tcc1_, Tcc0Tcc1Clock, TCC1_EV0, (1 << 24) - 1),
+    TccOnePulse2: (TCC2, tcc2_, Tcc2Tc3Clock, TCC2_EV0, u16::max_value() as u32),
+}
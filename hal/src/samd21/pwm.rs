@@ -1,9 +1,16 @@
 use crate::clock;
+use crate::dmac::{
+    self,
+    channel::{Interrupts, TriggerSource},
+    transfer::{ReadBuffer, WriteBuffer},
+    Transfer,
+};
+use crate::events;
 use crate::hal::{Pwm, PwmPin};
 use crate::time::Hertz;
 use crate::timer::TimerParams;
 
-use crate::target_device::{PM, TC3, TC4, TC5, TCC0, TCC1, TCC2};
+use crate::target_device::{tcc0, PM, TC3, TC4, TC5, TCC0, TCC1, TCC2};
 #[cfg(feature = "atsamd21j18a")]
 use crate::target_device::{TC6, TC7};
 
@@ -145,8 +152,122 @@ pub enum Channel {
     _3,
 }
 
+/// How the counter runs within a period
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Alignment {
+    /// Single-slope PWM, the counter counts up to the period and wraps
+    Edge,
+    /// Dual-slope PWM, the counter counts up to the period and back down so
+    /// the pulses are centered in the period
+    Center,
+}
+
+/// A recoverable fault, fed by the match or capture event input with the
+/// same index. The TCC resumes once the fault input goes away.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecoverableFault {
+    A,
+    B,
+}
+
+/// A non-recoverable fault, fed by the timer event input with the same
+/// index. The outputs stay in their fault state until the fault is cleared.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NonRecoverableFault {
+    Input0,
+    Input1,
+}
+
+/// What the counter does while a recoverable fault is active
+pub type FaultHalt = tcc0::fctrla::HALT_A;
+
+/// Extra resolution from dithering. The low 4, 5 or 6 bits of the period
+/// and duty values count the periods out of 16, 32 or 64 that are one tick
+/// longer, and the rest is the usual count in ticks.
+pub type Dithering = tcc0::ctrla::RESOLUTION_A;
+
+/// A buffered compare register of a TCC, the destination of the DMA
+/// transfers started by `feed_duty_dma`
+pub struct DutyRegister {
+    address: usize,
+}
+
+unsafe impl WriteBuffer for DutyRegister {
+    type Beat = u32;
+
+    fn dma_ptr(&mut self) -> *mut u32 {
+        self.address as *mut u32
+    }
+
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
+
+/// Duty values being fed to a TCC channel by DMA, as returned by
+/// `feed_duty_dma`.
+pub struct PwmDutyDma<PWM, Ch, B> {
+    pwm: PWM,
+    transfer: Transfer<Ch, B, DutyRegister>,
+}
+
+impl<PWM, Ch, B> PwmDutyDma<PWM, Ch, B>
+where
+    Ch: dmac::Channel,
+{
+    /// Whether the DMAC has written the last duty value, which takes effect
+    /// at the next update.
+    pub fn is_complete(&self) -> bool {
+        self.transfer.is_complete()
+    }
+
+    /// Block until the last duty value has been written, then release the
+    /// PWM, channel and buffer. If the DMAC stopped with an error the duty
+    /// stays at the last value written.
+    pub fn wait(self) -> Result<(PWM, Ch, B), (dmac::transfer::Error, PWM, Ch, B)> {
+        match self.transfer.wait() {
+            Ok((channel, buffer, _)) => Ok((self.pwm, channel, buffer)),
+            Err((error, channel, buffer, _)) => Err((error, self.pwm, channel, buffer)),
+        }
+    }
+
+    /// Stop feeding duty values, then release the PWM, channel and buffer.
+    /// The duty stays at the last value written.
+    pub fn stop(self) -> (PWM, Ch, B) {
+        let (channel, buffer, _) = self.transfer.stop();
+
+        (self.pwm, channel, buffer)
+    }
+}
+
+fn dither_bits(resolution: u8) -> u32 {
+    match resolution {
+        1 => 4,
+        2 => 5,
+        3 => 6,
+        _ => 0,
+    }
+}
+
+fn tcc_divider(prescaler: u8) -> u32 {
+    match prescaler {
+        0 => 1,
+        1 => 2,
+        2 => 4,
+        3 => 8,
+        4 => 16,
+        5 => 64,
+        6 => 256,
+        _ => 1024,
+    }
+}
+
 macro_rules! pwm_tcc {
-    ($($TYPE:ident: ($TCC:ident, $clock:ident, $apmask:ident, $apbits:ident, $wrapper:ident),)+) => {
+    ($($TYPE:ident: ($TCC:ident, $clock:ident, $apmask:ident, $apbits:ident, $wrapper:ident, $ovf:ident, $fault_a:ident, $fault_b:ident, $fault_0:ident, $fault_1:ident),)+) => {
         $(
 
 pub struct $TYPE {
@@ -197,6 +318,270 @@ impl $TYPE {
             tcc,
         }
     }
+
+    pub fn alignment(&self) -> Alignment {
+        if self.tcc.wave.read().wavegen().is_npwm() {
+            Alignment::Edge
+        } else {
+            Alignment::Center
+        }
+    }
+
+    /// Switch between single and dual-slope PWM, keeping the period. A
+    /// dual-slope period takes twice as many ticks, so the period register
+    /// is halved and the duties must be set again afterwards.
+    pub fn set_alignment(&mut self, alignment: Alignment) {
+        if self.alignment() == alignment {
+            return;
+        }
+
+        let bits = self.dither_bits();
+        let top = self.tcc.per().read().bits() >> bits;
+        let top = match alignment {
+            Alignment::Edge => top * 2,
+            Alignment::Center => top / 2,
+        };
+        let top = top << bits;
+
+        self.tcc.wave.modify(|_, w| match alignment {
+            Alignment::Edge => w.wavegen().npwm(),
+            // Update the buffered registers at the bottom of each period
+            Alignment::Center => w.wavegen().dsbottom(),
+        });
+        while self.tcc.syncbusy.read().wave().bit_is_set() {}
+        self.tcc.per().write(|w| unsafe { w.bits(top) });
+        while self.tcc.syncbusy.read().per().bit_is_set() {}
+    }
+
+    /// Write the duty of `channel` to its buffer register, it takes effect at
+    /// the next update so the current period isn't cut short.
+    pub fn set_duty_buffered(&mut self, channel: Channel, duty: u32) {
+        self.tcc.ccb()[channel as usize].write(|w| unsafe { w.ccb().bits(duty) });
+    }
+
+    /// Write the period to its buffer register, it takes effect at the next
+    /// update. The prescaler isn't changed so the period must be reachable
+    /// with the current one.
+    pub fn set_period_buffered<P: Into<Hertz>>(&mut self, period: P) {
+        let period = period.into();
+        let divisor = tcc_divider(self.tcc.ctrla.read().prescaler().bits());
+        let ticks = self.clock_freq.0 / divisor / period.0.max(1);
+        let top = match self.alignment() {
+            Alignment::Edge => ticks.saturating_sub(1),
+            Alignment::Center => ticks / 2,
+        };
+
+        self.tcc.perb().write(|w| unsafe { w.perb().bits(top << self.dither_bits()) });
+    }
+
+    /// Hold back the buffered values at updates, so that several of them
+    /// can be written and then take effect together after `unlock_update`.
+    pub fn lock_update(&mut self) {
+        self.tcc.ctrlbset.write(|w| w.lupd().set_bit());
+        while self.tcc.syncbusy.read().ctrlb().bit_is_set() {}
+    }
+
+    /// Let the buffered values take effect at the next update.
+    pub fn unlock_update(&mut self) {
+        self.tcc.ctrlbclr.write(|w| w.lupd().set_bit());
+        while self.tcc.syncbusy.read().ctrlb().bit_is_set() {}
+    }
+
+    /// Copy the buffered values into the period and compare registers now
+    /// rather than at the next update.
+    pub fn force_update(&mut self) {
+        self.tcc.ctrlbset.write(|w| w.cmd().update());
+        while self.tcc.syncbusy.read().ctrlb().bit_is_set() {}
+    }
+
+    /// Feed the duty values in `duties` to the buffer register of `channel`
+    /// with DMA on `dma`, one value per period. The PWM, channel and buffer
+    /// are handed back by the returned transfer.
+    pub fn feed_duty_dma<Ch, B>(self, channel: Channel, dma: Ch, duties: B, interrupts: Interrupts) -> PwmDutyDma<Self, Ch, B>
+    where
+        Ch: dmac::Channel,
+        B: ReadBuffer<Beat = u32>,
+    {
+        let register = DutyRegister {
+            address: &self.tcc.ccb()[channel as usize] as *const _ as usize,
+        };
+
+        // Each overflow pulls the next duty, which is applied at the update
+        // that follows
+        let transfer = Transfer::start(
+            dma,
+            duties,
+            register,
+            TriggerSource::$ovf,
+            interrupts,
+        );
+
+        PwmDutyDma {
+            pwm: self,
+            transfer,
+        }
+    }
+
+    fn dither_bits(&self) -> u32 {
+        dither_bits(self.tcc.ctrla.read().resolution().bits())
+    }
+
+    /// Set a recoverable fault, returning the event user to subscribe to
+    /// the fault source. With `restart` the counter restarts when the fault
+    /// goes away, with `keep` the fault state lasts until the end of the
+    /// period. `filter` is the number of consecutive samples the fault
+    /// input must hold, up to 15.
+    pub fn recoverable_fault(&mut self, fault: RecoverableFault, halt: FaultHalt, restart: bool, keep: bool, filter: u8) -> events::UserHandle<$TCC> {
+        self.configure(|tcc| {
+            match fault {
+                RecoverableFault::A => {
+                    tcc.fctrla.write(|w| unsafe {
+                        w.src().enable();
+                        w.halt().variant(halt);
+                        w.restart().bit(restart);
+                        w.keep().bit(keep);
+                        w.filterval().bits(filter)
+                    });
+                    tcc.evctrl.modify(|_, w| w.mcei0().set_bit());
+                },
+                RecoverableFault::B => {
+                    tcc.fctrlb.write(|w| unsafe {
+                        w.src().enable();
+                        // FCTRLB has its own copy of the FCTRLA types
+                        w.halt().bits(halt.into());
+                        w.restart().bit(restart);
+                        w.keep().bit(keep);
+                        w.filterval().bits(filter)
+                    });
+                    tcc.evctrl.modify(|_, w| w.mcei1().set_bit());
+                },
+            }
+        });
+
+        events::UserHandle::new(match fault {
+            RecoverableFault::A => events::User::$fault_a,
+            RecoverableFault::B => events::User::$fault_b,
+        })
+    }
+
+    /// Set a non-recoverable fault, returning the event user to subscribe
+    /// to the fault source. While the fault is active output WO[n] is forced
+    /// to bit n of `levels` if bit n of `outputs` is set. `filter` is the
+    /// number of consecutive samples the fault input must hold, up to 15.
+    pub fn non_recoverable_fault(&mut self, input: NonRecoverableFault, outputs: u8, levels: u8, filter: u8) -> events::UserHandle<$TCC> {
+        self.configure(|tcc| {
+            // NREn are bits 0 to 7, NRVn bits 8 to 15 and FILTERVALn four
+            // bits each from bit 24
+            let filter_shift = match input {
+                NonRecoverableFault::Input0 => 24,
+                NonRecoverableFault::Input1 => 28,
+            };
+            tcc.drvctrl.modify(|r, w| unsafe {
+                let mut bits = r.bits() & !(0xf << filter_shift) & !0xffff;
+                bits |= outputs as u32;
+                bits |= (levels as u32) << 8;
+                bits |= ((filter & 0xf) as u32) << filter_shift;
+                w.bits(bits)
+            });
+
+            tcc.evctrl.modify(|_, w| match input {
+                NonRecoverableFault::Input0 => {
+                    w.tcei0().set_bit();
+                    w.evact0().fault()
+                },
+                NonRecoverableFault::Input1 => {
+                    w.tcei1().set_bit();
+                    w.evact1().fault()
+                },
+            });
+        });
+
+        events::UserHandle::new(match input {
+            NonRecoverableFault::Input0 => events::User::$fault_0,
+            NonRecoverableFault::Input1 => events::User::$fault_1,
+        })
+    }
+
+    pub fn is_recoverable_fault(&self, fault: RecoverableFault) -> bool {
+        let flags = self.tcc.intflag.read();
+        match fault {
+            RecoverableFault::A => flags.faulta().bit_is_set(),
+            RecoverableFault::B => flags.faultb().bit_is_set(),
+        }
+    }
+
+    /// Clear the fault flag, and resume the counter after a fault with the
+    /// `SW` halt action.
+    pub fn clear_recoverable_fault(&mut self, fault: RecoverableFault) {
+        match fault {
+            RecoverableFault::A => {
+                self.tcc.intflag.write(|w| w.faulta().set_bit());
+                self.tcc.status.write(|w| w.faulta().set_bit());
+            },
+            RecoverableFault::B => {
+                self.tcc.intflag.write(|w| w.faultb().set_bit());
+                self.tcc.status.write(|w| w.faultb().set_bit());
+            },
+        }
+    }
+
+    pub fn is_non_recoverable_fault(&self, input: NonRecoverableFault) -> bool {
+        let flags = self.tcc.intflag.read();
+        match input {
+            NonRecoverableFault::Input0 => flags.fault0().bit_is_set(),
+            NonRecoverableFault::Input1 => flags.fault1().bit_is_set(),
+        }
+    }
+
+    /// Clear the fault flag and release the outputs, which only works once
+    /// the fault input has gone away.
+    pub fn clear_non_recoverable_fault(&mut self, input: NonRecoverableFault) {
+        match input {
+            NonRecoverableFault::Input0 => {
+                self.tcc.intflag.write(|w| w.fault0().set_bit());
+                self.tcc.status.write(|w| w.fault0().set_bit());
+            },
+            NonRecoverableFault::Input1 => {
+                self.tcc.intflag.write(|w| w.fault1().set_bit());
+                self.tcc.status.write(|w| w.fault1().set_bit());
+            },
+        }
+    }
+
+    /// Interrupt when any fault is detected.
+    pub fn enable_fault_interrupt(&mut self) {
+        self.tcc.intenset.write(|w| {
+            w.faulta().set_bit();
+            w.faultb().set_bit();
+            w.fault0().set_bit();
+            w.fault1().set_bit()
+        });
+    }
+
+    pub fn disable_fault_interrupt(&mut self) {
+        self.tcc.intenclr.write(|w| {
+            w.faulta().set_bit();
+            w.faultb().set_bit();
+            w.fault0().set_bit();
+            w.fault1().set_bit()
+        });
+    }
+
+    /// The fault, event and output registers can only be written while the
+    /// TCC is disabled, it is enabled again afterwards if it was running.
+    fn configure<F: FnOnce(&$TCC)>(&mut self, f: F) {
+        let enabled = self.tcc.ctrla.read().enable().bit_is_set();
+
+        self.tcc.ctrla.modify(|_, w| w.enable().clear_bit());
+        while self.tcc.syncbusy.read().enable().bit_is_set() {}
+
+        f(&self.tcc);
+
+        if enabled {
+            self.tcc.ctrla.modify(|_, w| w.enable().set_bit());
+            while self.tcc.syncbusy.read().enable().bit_is_set() {}
+        }
+    }
 }
 
 impl Pwm for $TYPE {
@@ -213,9 +598,14 @@ impl Pwm for $TYPE {
     }
 
     fn get_period(&self) -> Self::Time {
-        let divisor = self.tcc.ctrla.read().prescaler().bits();
-        let top = self.tcc.per().read().bits();
-        Hertz(self.clock_freq.0 / divisor as u32 / (top + 1) as u32)
+        let divisor = tcc_divider(self.tcc.ctrla.read().prescaler().bits());
+        let top = self.tcc.per().read().bits() >> self.dither_bits();
+        // A dual-slope period counts up to the top and back down
+        let ticks = match self.alignment() {
+            Alignment::Edge => top + 1,
+            Alignment::Center => top * 2,
+        };
+        Hertz(self.clock_freq.0 / divisor / ticks.max(1))
     }
 
     fn get_duty(&self, channel: Self::Channel) -> Self::Duty {
@@ -240,6 +630,10 @@ impl Pwm for $TYPE {
     {
         let period = period.into();
         let params = TimerParams::new(period, self.clock_freq.0);
+        let cycles = match self.alignment() {
+            Alignment::Edge => params.cycles,
+            Alignment::Center => params.cycles / 2,
+        };
         self.tcc.ctrla.modify(|_, w| w.enable().clear_bit());
         self.tcc.ctrla.modify(|_, w| {
             match params.divider {
@@ -255,15 +649,108 @@ impl Pwm for $TYPE {
             }
         });
         self.tcc.ctrla.modify(|_, w| w.enable().set_bit());
-        self.tcc.per().write(|w| unsafe { w.bits(params.cycles as u32) });
+        self.tcc.per().write(|w| unsafe { w.bits((cycles as u32) << self.dither_bits()) });
         while self.tcc.syncbusy.read().per().bit() {}
     }
 }
 
 )+}}
 
+/// Dead time insertion, only TCC0 has the waveform extension
+macro_rules! pwm_tcc_dead_time {
+    ($($TYPE:ident,)+) => {
+        $(
+
+impl $TYPE {
+    /// Drive WO[n] and WO[n + 4] from `channel` as a complementary pair,
+    /// with `low_side` ticks between the high side turning off and the low
+    /// side turning on and `high_side` ticks the other way round. The dead
+    /// times are shared by all channels.
+    pub fn dead_time(&mut self, channel: Channel, low_side: u8, high_side: u8) {
+        self.configure(|tcc| {
+            tcc.wexctrl.modify(|r, w| unsafe {
+                // DTIENn are bits 8 to 11
+                w.bits(r.bits() | 1 << (8 + channel as u32));
+                w.dtls().bits(low_side);
+                w.dths().bits(high_side)
+            });
+        });
+    }
+
+    pub fn disable_dead_time(&mut self, channel: Channel) {
+        self.configure(|tcc| {
+            tcc.wexctrl.modify(|r, w| unsafe {
+                w.bits(r.bits() & !(1 << (8 + channel as u32)))
+            });
+        });
+    }
+}
+
+)+}}
+
+/// Dithering, on TCC0 and TCC1
+macro_rules! pwm_tcc_dithering {
+    ($($TYPE:ident,)+) => {
+        $(
+
+impl $TYPE {
+    /// Set the dithering resolution, keeping the period. The duties must be
+    /// set again afterwards, with the new number of fractional bits.
+    pub fn set_dithering(&mut self, dithering: Dithering) {
+        let top = self.tcc.per().read().bits() >> self.dither_bits();
+        let bits = dither_bits(dithering.into());
+
+        self.configure(|tcc| {
+            tcc.ctrla.modify(|_, w| w.resolution().variant(dithering));
+        });
+
+        self.tcc.per().write(|w| unsafe { w.bits(top << bits) });
+        while self.tcc.syncbusy.read().per().bit_is_set() {}
+    }
+}
+
+)+}}
+
+/// Pattern generation, on TCC0 and TCC1
+macro_rules! pwm_tcc_pattern {
+    ($($TYPE:ident,)+) => {
+        $(
+
+impl $TYPE {
+    /// Override WO[n] with bit n of `levels` if bit n of `outputs` is set.
+    /// The pattern is buffered and takes effect at the next update, so a
+    /// sequence of patterns can be stepped through once per period.
+    pub fn set_pattern(&mut self, outputs: u8, levels: u8) {
+        self.tcc.pattb.write(|w| unsafe {
+            // PGEBn are bits 0 to 7 and PGVBn bits 8 to 15
+            w.bits((outputs as u16) | (levels as u16) << 8)
+        });
+    }
+
+    /// Stop overriding the outputs at the next update.
+    pub fn clear_pattern(&mut self) {
+        self.tcc.pattb.write(|w| unsafe { w.bits(0) });
+    }
+}
+
+)+}}
+
 pwm_tcc! {
-    Pwm0: (TCC0, Tcc0Tcc1Clock, apbcmask, tcc0_, Pwm0Wrapper),
-    Pwm1: (TCC1, Tcc0Tcc1Clock, apbcmask, tcc1_, Pwm1Wrapper),
-    Pwm2: (TCC2, Tcc2Tc3Clock, apbcmask, tcc2_, Pwm2Wrapper),
+    Pwm0: (TCC0, Tcc0Tcc1Clock, apbcmask, tcc0_, Pwm0Wrapper, Tcc0Ovf, TCC0_MC0, TCC0_MC1, TCC0_EV0, TCC0_EV1),
+    Pwm1: (TCC1, Tcc0Tcc1Clock, apbcmask, tcc1_, Pwm1Wrapper, Tcc1Ovf, TCC1_MC0, TCC1_MC1, TCC1_EV0, TCC1_EV1),
+    Pwm2: (TCC2, Tcc2Tc3Clock, apbcmask, tcc2_, Pwm2Wrapper, Tcc2Ovf, TCC2_MC0, TCC2_MC1, TCC2_EV0, TCC2_EV1),
+}
+
+pwm_tcc_dead_time! {
+    Pwm0,
+}
+
+pwm_tcc_dithering! {
+    Pwm0,
+    Pwm1,
+}
+
+pwm_tcc_pattern! {
+    Pwm0,
+    Pwm1,
 }
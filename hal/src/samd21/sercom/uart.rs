@@ -1,5 +1,6 @@
 use crate::clock;
 use crate::time::Hertz;
+use crate::uart_buffer::{Received, UartRx};
 use crate::hal::blocking::serial::{write::Default, Write};
 use crate::hal::serial;
 use nb;
@@ -302,6 +303,55 @@ macro_rules! uart {
                 }
             }
 
+            unsafe impl<RX, TX, RTS, CTS> UartRx for $Type<RX, TX, RTS, CTS> {
+                fn enable_rx_interrupts(&mut self) {
+                    self.usart().intenset.write(|w| {
+                        w.rxc().set_bit();
+                        w.error().set_bit()
+                    });
+                }
+
+                fn disable_rx_interrupts(&mut self) {
+                    self.usart().intenclr.write(|w| {
+                        w.rxc().set_bit();
+                        w.error().set_bit()
+                    });
+                }
+
+                unsafe fn receive() -> Option<Received> {
+                    let usart = (*$SERCOM::ptr()).usart();
+
+                    // The error flag is cleared along with the byte it
+                    // belongs to, or here if there is no byte
+                    if usart.intflag.read().rxc().bit_is_clear() {
+                        usart.intflag.write(|w| w.error().set_bit());
+                        return None;
+                    }
+
+                    // The status applies to the byte at the head of the
+                    // receive buffer, so read it before the data
+                    let status = usart.status.read();
+                    let received = Received {
+                        data: 0,
+                        overrun: status.bufovf().bit_is_set(),
+                        frame: status.ferr().bit_is_set(),
+                        parity: status.perr().bit_is_set(),
+                    };
+
+                    usart.status.write(|w| {
+                        w.bufovf().set_bit();
+                        w.ferr().set_bit();
+                        w.perr().set_bit()
+                    });
+                    usart.intflag.write(|w| w.error().set_bit());
+
+                    Some(Received {
+                        data: usart.data.read().bits() as u8,
+                        .. received
+                    })
+                }
+            }
+
             impl<RX, TX, RTS, CTS> Default<u8> for $Type<RX, TX, RTS, CTS> {}
 
             impl<RX, TX, RTS, CTS> fmt::Write for $Type<RX, TX, RTS, CTS> {
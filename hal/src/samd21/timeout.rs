@@ -1,16 +1,15 @@
 use crate::{
     target_device,
     clock,
+    events,
+    timer::{Count8, Count16, Count32},
 };
 
-use core::ops::Deref;
-
 pub type Divider = target_device::tc3::count16::ctrla::PRESCALER_A;
 
-pub trait Count16 {
-    fn count16(&self) -> &target_device::tc3::COUNT16;
-}
-
+/// A TC counting down once from its start value each time its event input
+/// retriggers it, in 16-bit mode. See `Timeout8` for 8-bit mode and
+/// `Timeout32` for pairs of counters in 32-bit mode.
 pub struct Timeout<TC> {
     tc: TC,
 }
@@ -18,110 +17,306 @@ pub struct Timeout<TC> {
 impl<TC> Timeout<TC>
 where TC: Count16 {
     pub fn enable_overflow_interrupt(&mut self) {
-        self.tc.count16().intenset.write(|w| w.ovf().set_bit());
+        self.tc.count_16().intenset.write(|w| w.ovf().set_bit());
     }
 
     pub fn diable_overflow_interrupt(&mut self) {
-        self.tc.count16().intenclr.write(|w| w.ovf().set_bit());
+        self.tc.count_16().intenclr.write(|w| w.ovf().set_bit());
     }
 
     pub fn clear_overflow_interrupt(&mut self) {
-        self.tc.count16().intflag.modify(|_, w| w.ovf().set_bit());
+        self.tc.count_16().intflag.modify(|_, w| w.ovf().set_bit());
     }
 
     pub fn enable_overflow_event(&mut self) {
-        self.tc.count16().evctrl.modify(|_, w| {
+        self.tc.count_16().evctrl.modify(|_, w| {
             w.ovfeo().set_bit()
         });
     }
 
     pub fn retrigger(&self) {
-        self.tc.count16().ctrlbset.write(|w| {
+        self.tc.count_16().ctrlbset.write(|w| {
             w.cmd().retrigger()
         });
-        while self.tc.count16().status.read().syncbusy().bit_is_set() {
+        while self.tc.count_16().status.read().syncbusy().bit_is_set() {
             cortex_m::asm::nop();
         }
     }
 
     pub fn count(&self) -> u16 {
-        self.tc.count16().count.read().bits()
+        self.tc.count_16().count.read().bits()
     }
 
     pub fn clear(&mut self) {
-        self.tc.count16().count.reset()
+        self.tc.count_16().count.reset()
     }
 }
 
-macro_rules! timer {
-    ($($TYPE:ident: ($TC:ident, $pm:ident, $clock:ident),)+) => {
-        $(
-impl Count16 for target_device::$TC {
-    fn count16(&self) -> &target_device::tc3::COUNT16 {
-        let register: &target_device::tc3::RegisterBlock = self.deref();
-        register.count16()
+/// A TC timing out in 8-bit mode.
+pub struct Timeout8<TC> {
+    tc: TC,
+}
+
+impl<TC> Timeout8<TC>
+where TC: Count8 {
+    pub fn enable_overflow_interrupt(&mut self) {
+        self.tc.count_8().intenset.write(|w| w.ovf().set_bit());
+    }
+
+    pub fn disable_overflow_interrupt(&mut self) {
+        self.tc.count_8().intenclr.write(|w| w.ovf().set_bit());
+    }
+
+    pub fn clear_overflow_interrupt(&mut self) {
+        self.tc.count_8().intflag.write(|w| w.ovf().set_bit());
+    }
+
+    pub fn enable_overflow_event(&mut self) {
+        self.tc.count_8().evctrl.modify(|_, w| {
+            w.ovfeo().set_bit()
+        });
+    }
+
+    pub fn retrigger(&self) {
+        self.tc.count_8().ctrlbset.write(|w| {
+            w.cmd().retrigger()
+        });
+        while self.tc.count_8().status.read().syncbusy().bit_is_set() {
+            cortex_m::asm::nop();
+        }
+    }
+
+    pub fn count(&self) -> u8 {
+        self.tc.count_8().count.read().bits()
+    }
+
+    pub fn clear(&mut self) {
+        self.tc.count_8().count.reset()
     }
 }
 
-impl Timeout<target_device::$TC>
-where target_device::$TC: Count16
-{
-    pub fn $pm(_clock: &clock::$clock, tc: target_device::$TC, divider: Divider, start: u16, pm: &mut target_device::PM) -> Self {
-        pm.apbcmask.modify(|_, w| w.$pm().set_bit());
+/// A pair of TCs timing out in 32-bit mode, retriggered through the master's
+/// event input. The slave is held so it can't be used while the pair is
+/// counting.
+pub struct Timeout32<Master, Slave> {
+    tc: Master,
+    slave: Slave,
+}
+
+impl<Master, Slave> Timeout32<Master, Slave>
+where Master: Count32 {
+    pub fn enable_overflow_interrupt(&mut self) {
+        self.tc.count_32().intenset.write(|w| w.ovf().set_bit());
+    }
 
-        let counter = tc.count16();
-        counter.evctrl.modify(|_, w| {
+    pub fn disable_overflow_interrupt(&mut self) {
+        self.tc.count_32().intenclr.write(|w| w.ovf().set_bit());
+    }
+
+    pub fn clear_overflow_interrupt(&mut self) {
+        self.tc.count_32().intflag.write(|w| w.ovf().set_bit());
+    }
+
+    pub fn enable_overflow_event(&mut self) {
+        self.tc.count_32().evctrl.modify(|_, w| {
+            w.ovfeo().set_bit()
+        });
+    }
+
+    pub fn retrigger(&self) {
+        self.tc.count_32().ctrlbset.write(|w| {
+            w.cmd().retrigger()
+        });
+        while self.tc.count_32().status.read().syncbusy().bit_is_set() {
+            cortex_m::asm::nop();
+        }
+    }
+
+    pub fn count(&self) -> u32 {
+        self.tc.count_32().count.read().bits()
+    }
+
+    pub fn clear(&mut self) {
+        self.tc.count_32().count.reset()
+    }
+
+    /// Disable the pair and release the two counters.
+    pub fn free(self) -> (Master, Slave) {
+        let counter = self.tc.count_32();
+        counter.ctrla.modify(|_, w| w.enable().clear_bit());
+        while counter.status.read().syncbusy().bit_is_set() {
+            cortex_m::asm::nop();
+        }
+
+        (self.tc, self.slave)
+    }
+}
+
+/// Configure a counter to count down once from `start` on each retrigger,
+/// for any of the three counter sizes. `$mode` sets the size and goes first
+/// as it is enable protected and sets the size of the other registers.
+macro_rules! configure_timeout {
+    ($counter:ident, $mode:ident, $divider:ident, $start:ident) => {
+        $counter.ctrla.modify(|_, w| w.mode().$mode());
+        $counter.evctrl.modify(|_, w| {
             w.tcei().set_bit();
             w.evact().retrigger()
         });
 
-        counter.cc[0].write(|w| unsafe {
-            w.bits(start)
+        $counter.cc[0].write(|w| unsafe {
+            w.bits($start)
         });
-        while counter.status.read().syncbusy().bit_is_set() {
+        while $counter.status.read().syncbusy().bit_is_set() {
             cortex_m::asm::nop();
         }
 
         // Ensure first retrigger starts from the top value
-        counter.count.write(|w| unsafe {
-            w.bits(start)
+        $counter.count.write(|w| unsafe {
+            w.bits($start)
         });
-        while counter.status.read().syncbusy().bit_is_set() {
+        while $counter.status.read().syncbusy().bit_is_set() {
             cortex_m::asm::nop();
         }
 
-        counter.ctrlbset.write(|w| {
+        $counter.ctrlbset.write(|w| {
             // Count down from TOP -> 0
             w.dir().set_bit();
             // Once, don't loop back
             w.oneshot().set_bit()
         });
-        while counter.status.read().syncbusy().bit_is_set() {
+        while $counter.status.read().syncbusy().bit_is_set() {
             cortex_m::asm::nop();
         }
 
-        counter.ctrla.modify(|_, w| {
+        $counter.ctrla.modify(|_, w| {
             w.runstdby().set_bit();
-            w.prescaler().variant(divider);
+            // Each counter size has its own copy of the count16 prescaler
+            w.prescaler().bits($divider.into());
             // Use cc[0] as TOP
             w.wavegen().mfrq();
             w.enable().set_bit()
         });
-        while counter.status.read().syncbusy().bit_is_set() {
+        while $counter.status.read().syncbusy().bit_is_set() {
             cortex_m::asm::nop();
         }
+    };
+}
+
+macro_rules! timer {
+    ($($TYPE:ident: ($TC:ident, $pm:ident, $clock:ident, $user:ident, $overflow:ident),)+) => {
+        $(
+pub type $TYPE = Timeout<target_device::$TC>;
+
+impl Timeout<target_device::$TC>
+{
+    pub fn $pm(_clock: &clock::$clock, tc: target_device::$TC, divider: Divider, start: u16, pm: &mut target_device::PM) -> Self {
+        pm.apbcmask.modify(|_, w| w.$pm().set_bit());
+
+        let counter = tc.count_16();
+        configure_timeout!(counter, count16, divider, start);
 
         Self {
             tc,
         }
     }
+
+    /// Enable the overflow event output, returning the generator to publish
+    /// on an event channel.
+    pub fn overflow_event(&mut self) -> events::GeneratorHandle<target_device::$TC> {
+        self.enable_overflow_event();
+        events::GeneratorHandle::new(events::Generator::$overflow)
+    }
+}
+
+/// The timeout's event input, subscribe it to the channel whose events should
+/// retrigger the timeout
+impl events::EventUser for Timeout<target_device::$TC> {
+    fn event_user(&self) -> events::User {
+        events::User::$user
+    }
+}
+
+impl Timeout8<target_device::$TC>
+{
+    pub fn $pm(_clock: &clock::$clock, tc: target_device::$TC, divider: Divider, start: u8, pm: &mut target_device::PM) -> Self {
+        pm.apbcmask.modify(|_, w| w.$pm().set_bit());
+
+        let counter = tc.count_8();
+        configure_timeout!(counter, count8, divider, start);
+
+        Self {
+            tc,
+        }
+    }
+
+    /// Enable the overflow event output, returning the generator to publish
+    /// on an event channel.
+    pub fn overflow_event(&mut self) -> events::GeneratorHandle<target_device::$TC> {
+        self.enable_overflow_event();
+        events::GeneratorHandle::new(events::Generator::$overflow)
+    }
+}
+
+/// The timeout's event input, subscribe it to the channel whose events should
+/// retrigger the timeout
+impl events::EventUser for Timeout8<target_device::$TC> {
+    fn event_user(&self) -> events::User {
+        events::User::$user
+    }
+}
+        )+
+    }
+}
+
+macro_rules! timer32 {
+    ($($TYPE:ident: ($MASTER:ident, $SLAVE:ident, $pm:ident, $master_pm:ident, $slave_pm:ident, $clock:ident, $user:ident, $overflow:ident),)+) => {
+        $(
+pub type $TYPE = Timeout32<target_device::$MASTER, target_device::$SLAVE>;
+
+impl Timeout32<target_device::$MASTER, target_device::$SLAVE>
+{
+    pub fn $pm(_clock: &clock::$clock, master: target_device::$MASTER, slave: target_device::$SLAVE, divider: Divider, start: u32, pm: &mut target_device::PM) -> Self {
+        // Both counters of the pair need their bus clock
+        pm.apbcmask.modify(|_, w| {
+            w.$master_pm().set_bit();
+            w.$slave_pm().set_bit()
+        });
+
+        // The slave follows the master in 32-bit mode
+        let counter = master.count_32();
+        configure_timeout!(counter, count32, divider, start);
+
+        Self {
+            tc: master,
+            slave,
+        }
+    }
+
+    /// Enable the overflow event output, returning the generator to publish
+    /// on an event channel.
+    pub fn overflow_event(&mut self) -> events::GeneratorHandle<target_device::$MASTER> {
+        self.enable_overflow_event();
+        events::GeneratorHandle::new(events::Generator::$overflow)
+    }
+}
+
+/// The master's event input, subscribe it to the channel whose events should
+/// retrigger the timeout
+impl events::EventUser for Timeout32<target_device::$MASTER, target_device::$SLAVE> {
+    fn event_user(&self) -> events::User {
+        events::User::$user
+    }
 }
         )+
     }
 }
 
 timer! {
-    Timeout3: (TC3, tc3_, Tcc2Tc3Clock),
-    Timeout4: (TC4, tc4_, Tc4Tc5Clock),
-    Timeout5: (TC5, tc5_, Tc4Tc5Clock),
+    Timeout3: (TC3, tc3_, Tcc2Tc3Clock, TC3, TC3_OVF),
+    Timeout4: (TC4, tc4_, Tc4Tc5Clock, TC4, TC4_OVF),
+    Timeout5: (TC5, tc5_, Tc4Tc5Clock, TC5, TC5_OVF),
+}
+
+timer32! {
+    Timeout4_5: (TC4, TC5, tc4_tc5, tc4_, tc5_, Tc4Tc5Clock, TC4, TC4_OVF),
 }
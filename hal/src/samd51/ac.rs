@@ -0,0 +1,413 @@
+//! The two analog comparators.
+//!
+//! Each comparator compares a positive input, one of the `AIN` pins or a
+//! fraction of VDD, with a negative input, another pin or an internal source:
+//! ground, a fraction of VDD, the bandgap or DAC0. Comparators run
+//! continuously or take a single comparison when started, and the pair can
+//! be combined to watch whether an input is inside or outside a window. Their
+//! outputs raise interrupts and events, so a comparator can shut down a TCC
+//! through its fault inputs, or wake the core from standby.
+//!
+//! ```ignore
+//! let mut ac = Ac::init(&mut peripherals.MCLK, ac_clock, peripherals.AC);
+//! let mut ain0 = pins.a4.into_function_b(&mut pins.port);
+//! ac.inputs(Comparator::_0, &mut ain0, &mut VddScaler);
+//! // Compare with half of VDD
+//! ac.scaler(Comparator::_0, 31);
+//! ac.enable(Comparator::_0);
+//! let above = ac.state(Comparator::_0);
+//! ```
+use crate::{
+    target_device,
+    calibration,
+    clock,
+    gpio,
+    events,
+};
+
+/// The hysteresis level, which stops noise on slowly changing inputs
+/// toggling the output
+pub type Hysteresis = target_device::ac::compctrl::HYST_A;
+/// The output transitions that set a comparator's interrupt flag
+pub type InterruptMode = target_device::ac::compctrl::INTSEL_A;
+/// The majority filter applied to a comparator's output
+pub type Filter = target_device::ac::compctrl::FLEN_A;
+/// The window states that set the window interrupt flag
+pub type WindowMode = target_device::ac::winctrl::WINTSEL0_A;
+/// Where the window input is relative to the window
+pub type WindowState = target_device::ac::statusa::WSTATE0_A;
+pub type PositiveMux = target_device::ac::compctrl::MUXPOS_A;
+pub type NegativeMux = target_device::ac::compctrl::MUXNEG_A;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Comparator {
+    _0,
+    _1,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Mode {
+    /// Compare continuously, the output follows the inputs
+    Continuous,
+    /// Compare once each time the comparator is started, by `start` or an
+    /// event
+    SingleShot,
+}
+
+/// The interrupt flags of the AC
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Flag {
+    /// Comparator 0 matched its interrupt mode
+    Comparator0 = 0,
+    /// Comparator 1 matched its interrupt mode
+    Comparator1 = 1,
+    /// The window matched its window mode
+    Window = 4,
+}
+
+impl Flag {
+    fn mask(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+/// An input that can be connected to the positive side of a comparator. You
+/// should not implement this trait yourself, only the implementations in this
+/// module make sense.
+pub trait PositiveInput {
+    const MUX: PositiveMux;
+}
+
+/// An input that can be connected to the negative side of a comparator. You
+/// should not implement this trait yourself, only the implementations in this
+/// module make sense.
+pub trait NegativeInput {
+    const MUX: NegativeMux;
+}
+
+/// Ground
+pub struct Ground;
+
+/// VDD divided by the comparator's scaler, see `Ac::scaler`
+pub struct VddScaler;
+
+/// The 1.0V bandgap reference
+pub struct Bandgap;
+
+/// The DAC0 output, enabled with `Dac::enable_channel`
+pub struct DacOutput;
+
+impl PositiveInput for VddScaler {
+    const MUX: PositiveMux = PositiveMux::VSCALE;
+}
+
+impl NegativeInput for Ground {
+    const MUX: NegativeMux = NegativeMux::GND;
+}
+
+impl NegativeInput for VddScaler {
+    const MUX: NegativeMux = NegativeMux::VSCALE;
+}
+
+impl NegativeInput for Bandgap {
+    const MUX: NegativeMux = NegativeMux::BANDGAP;
+}
+
+impl NegativeInput for DacOutput {
+    const MUX: NegativeMux = NegativeMux::DAC;
+}
+
+macro_rules! ac_pins {
+    ($($pin:ident: ($pos:ident, $neg:ident),)+) => {
+        $(
+            impl PositiveInput for gpio::$pin<gpio::PfB> {
+                const MUX: PositiveMux = PositiveMux::$pos;
+            }
+
+            impl NegativeInput for gpio::$pin<gpio::PfB> {
+                const MUX: NegativeMux = NegativeMux::$neg;
+            }
+        )+
+    }
+}
+
+ac_pins! {
+    Pa4: (PIN0, PIN0),
+    Pa5: (PIN1, PIN1),
+    Pa6: (PIN2, PIN2),
+    Pa7: (PIN3, PIN3),
+}
+
+pub struct Ac {
+    ac: target_device::AC,
+}
+
+impl Ac {
+    /// Reset and calibrate the AC and enable it, with both comparators
+    /// disabled.
+    pub fn init(mclk: &mut target_device::MCLK, _clock: clock::AcClock, ac: target_device::AC) -> Self {
+        mclk.apbcmask.modify(|_, w| w.ac_().set_bit());
+
+        ac.ctrla.write(|w| w.swrst().set_bit());
+        while ac.syncbusy.read().swrst().bit_is_set() {}
+
+        ac.calib.write(|w| unsafe { w.bias0().bits(calibration::ac_bias_cal()) });
+
+        let new = Ac { ac };
+
+        // The comparators only support high speed, the reset value is
+        // reserved
+        for &comparator in [Comparator::_0, Comparator::_1].iter() {
+            new.ac.compctrl[comparator as usize].write(|w| w.speed().high());
+            new.sync_comparator(comparator);
+        }
+
+        new.ac.ctrla.write(|w| w.enable().set_bit());
+        while new.ac.syncbusy.read().enable().bit_is_set() {}
+
+        new
+    }
+
+    /// Connect the inputs of `comparator`. Its output is high while the
+    /// positive input is above the negative input.
+    pub fn inputs<P: PositiveInput, N: NegativeInput>(&mut self, comparator: Comparator, _positive: &mut P, _negative: &mut N) {
+        self.configure(comparator, |w| {
+            w.muxpos().variant(P::MUX);
+            w.muxneg().variant(N::MUX)
+        });
+    }
+
+    /// Set the `VddScaler` input of `comparator` to VDD * (`value` + 1) / 64,
+    /// `value` is up to 63.
+    pub fn scaler(&mut self, comparator: Comparator, value: u8) {
+        self.ac.scaler[comparator as usize].write(|w| unsafe { w.value().bits(value.min(63)) });
+    }
+
+    /// Enable hysteresis on `comparator` at the given level, or disable it
+    /// with `None`.
+    pub fn hysteresis(&mut self, comparator: Comparator, hysteresis: Option<Hysteresis>) {
+        self.configure(comparator, |w| match hysteresis {
+            Some(level) => {
+                w.hyst().variant(level);
+                w.hysten().set_bit()
+            }
+            None => w.hysten().clear_bit(),
+        });
+    }
+
+    pub fn filter(&mut self, comparator: Comparator, filter: Filter) {
+        self.configure(comparator, |w| w.flen().variant(filter));
+    }
+
+    pub fn mode(&mut self, comparator: Comparator, mode: Mode) {
+        self.configure(comparator, |w| w.single().bit(mode == Mode::SingleShot));
+    }
+
+    /// Select the output transitions of `comparator` that set its interrupt
+    /// flag and generate its event.
+    pub fn interrupt_mode(&mut self, comparator: Comparator, mode: InterruptMode) {
+        self.configure(comparator, |w| w.intsel().variant(mode));
+    }
+
+    /// Keep `comparator` running in standby, so it can wake the core.
+    pub fn run_in_standby(&mut self, comparator: Comparator, enable: bool) {
+        self.configure(comparator, |w| w.runstdby().bit(enable));
+    }
+
+    /// Enable `comparator`, waiting for it to start up.
+    pub fn enable(&mut self, comparator: Comparator) {
+        self.ac.compctrl[comparator as usize].modify(|_, w| w.enable().set_bit());
+        self.sync_comparator(comparator);
+
+        while !self.is_ready(comparator) {}
+    }
+
+    pub fn disable(&mut self, comparator: Comparator) {
+        self.ac.compctrl[comparator as usize].modify(|_, w| w.enable().clear_bit());
+        self.sync_comparator(comparator);
+    }
+
+    /// Start a single shot comparison.
+    pub fn start(&mut self, comparator: Comparator) {
+        self.ac.ctrlb.write(|w| match comparator {
+            Comparator::_0 => w.start0().set_bit(),
+            Comparator::_1 => w.start1().set_bit(),
+        });
+    }
+
+    /// Whether the output of `comparator` is valid.
+    pub fn is_ready(&self, comparator: Comparator) -> bool {
+        let status = self.ac.statusb.read();
+        match comparator {
+            Comparator::_0 => status.ready0().bit_is_set(),
+            Comparator::_1 => status.ready1().bit_is_set(),
+        }
+    }
+
+    /// The output of `comparator`, true while the positive input is above the
+    /// negative input.
+    pub fn state(&self, comparator: Comparator) -> bool {
+        let status = self.ac.statusa.read();
+        match comparator {
+            Comparator::_0 => status.state0().bit_is_set(),
+            Comparator::_1 => status.state1().bit_is_set(),
+        }
+    }
+
+    /// Start a comparison and block until its result is ready. Only a single
+    /// shot comparator needs starting, a continuous one returns its current
+    /// output.
+    pub fn compare(&mut self, comparator: Comparator) -> bool {
+        self.start(comparator);
+        while !self.is_ready(comparator) {}
+        self.state(comparator)
+    }
+
+    /// Combine the comparators into a window, with the interrupt flag set as
+    /// the input enters or leaves it, or disable the window with `None`.
+    ///
+    /// The window input must be connected to the positive input of
+    /// comparator 0 and the negative input of comparator 1, the upper limit
+    /// to the negative input of comparator 0 and the lower limit to the
+    /// positive input of comparator 1.
+    pub fn window(&mut self, mode: Option<WindowMode>) {
+        self.ac.winctrl.write(|w| match mode {
+            Some(mode) => {
+                w.wintsel0().variant(mode);
+                w.wen0().set_bit()
+            }
+            None => w.wen0().clear_bit(),
+        });
+        while self.ac.syncbusy.read().winctrl().bit_is_set() {}
+    }
+
+    /// Where the window input is relative to the window.
+    pub fn window_state(&self) -> WindowState {
+        let state = self.ac.statusa.read().wstate0();
+        if state.is_above() {
+            WindowState::ABOVE
+        } else if state.is_below() {
+            WindowState::BELOW
+        } else {
+            WindowState::INSIDE
+        }
+    }
+
+    pub fn enable_interrupt(&mut self, flag: Flag) {
+        self.ac.intenset.write(|w| unsafe { w.bits(flag.mask()) });
+    }
+
+    pub fn disable_interrupt(&mut self, flag: Flag) {
+        self.ac.intenclr.write(|w| unsafe { w.bits(flag.mask()) });
+    }
+
+    pub fn is_set(&self, flag: Flag) -> bool {
+        self.ac.intflag.read().bits() & flag.mask() != 0
+    }
+
+    pub fn clear(&mut self, flag: Flag) {
+        self.ac.intflag.write(|w| unsafe { w.bits(flag.mask()) });
+    }
+
+    /// Enable the output event of `comparator`, returning the generator to
+    /// publish on an event channel. Through the event system a comparator can
+    /// drive a TCC fault input.
+    pub fn event(&mut self, comparator: Comparator) -> events::GeneratorHandle<target_device::AC> {
+        self.configure_events(|ac| {
+            ac.evctrl.modify(|_, w| match comparator {
+                Comparator::_0 => w.compeo0().set_bit(),
+                Comparator::_1 => w.compeo1().set_bit(),
+            });
+        });
+
+        events::GeneratorHandle::new(match comparator {
+            Comparator::_0 => events::Generator::AC_COMP0,
+            Comparator::_1 => events::Generator::AC_COMP1,
+        })
+    }
+
+    /// Enable the window event, returning the generator to publish on an
+    /// event channel.
+    pub fn window_event(&mut self) -> events::GeneratorHandle<target_device::AC> {
+        self.configure_events(|ac| {
+            ac.evctrl.modify(|_, w| w.wineo0().set_bit());
+        });
+        events::GeneratorHandle::new(events::Generator::AC_WIN0)
+    }
+
+    /// Start a single shot comparison on `comparator` with each event,
+    /// returning the user to subscribe to an event channel. With `invert` the
+    /// event input is inverted.
+    pub fn start_event(&mut self, comparator: Comparator, invert: bool) -> events::UserHandle<target_device::AC> {
+        self.configure_events(|ac| {
+            ac.evctrl.modify(|_, w| match comparator {
+                Comparator::_0 => {
+                    w.invei0().bit(invert);
+                    w.compei0().set_bit()
+                }
+                Comparator::_1 => {
+                    w.invei1().bit(invert);
+                    w.compei1().set_bit()
+                }
+            });
+        });
+
+        events::UserHandle::new(match comparator {
+            Comparator::_0 => events::User::AC_SOC0,
+            Comparator::_1 => events::User::AC_SOC1,
+        })
+    }
+
+    /// Disable the AC and release it.
+    pub fn free(self) -> target_device::AC {
+        self.ac.ctrla.modify(|_, w| w.enable().clear_bit());
+        while self.ac.syncbusy.read().enable().bit_is_set() {}
+
+        self.ac
+    }
+
+    /// COMPCTRL can only be written while the comparator is disabled, it is
+    /// enabled again afterwards if it was running.
+    fn configure<F>(&mut self, comparator: Comparator, f: F)
+    where
+        F: FnOnce(&mut target_device::ac::compctrl::W) -> &mut target_device::ac::compctrl::W,
+    {
+        let compctrl = &self.ac.compctrl[comparator as usize];
+        let enabled = compctrl.read().enable().bit_is_set();
+
+        compctrl.modify(|_, w| w.enable().clear_bit());
+        self.sync_comparator(comparator);
+
+        compctrl.modify(|_, w| f(w));
+        self.sync_comparator(comparator);
+
+        if enabled {
+            self.enable(comparator);
+        }
+    }
+
+    /// EVCTRL can only be written while the AC is disabled, it is enabled
+    /// again afterwards.
+    fn configure_events<F: FnOnce(&target_device::AC)>(&mut self, f: F) {
+        self.ac.ctrla.modify(|_, w| w.enable().clear_bit());
+        while self.ac.syncbusy.read().enable().bit_is_set() {}
+
+        f(&self.ac);
+
+        self.ac.ctrla.modify(|_, w| w.enable().set_bit());
+        while self.ac.syncbusy.read().enable().bit_is_set() {}
+    }
+
+    fn sync_comparator(&self, comparator: Comparator) {
+        loop {
+            let syncbusy = self.ac.syncbusy.read();
+            let busy = match comparator {
+                Comparator::_0 => syncbusy.compctrl0().bit_is_set(),
+                Comparator::_1 => syncbusy.compctrl1().bit_is_set(),
+            };
+            if !busy {
+                break;
+            }
+        }
+    }
+}
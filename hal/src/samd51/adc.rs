@@ -1,4 +1,12 @@
+use crate::calibration;
 use crate::clock::GenericClockController;
+use crate::dmac::{
+    self,
+    channel::{Interrupts, Settings, TriggerSource},
+    circular::{CircularTransfer, DoubleBuffer, Half, Overrun},
+    transfer::{ReadBuffer, WriteBuffer},
+    Transfer,
+};
 use crate::gpio::{
     Pa10, Pa11, Pa2, Pa3, Pa4, Pa5, Pa6, Pa7, Pa8, Pa9, Pb0, Pb1, Pb2, Pb3, Pb4, Pb5, Pb6, Pb7,
     Pb8, Pb9, PfB,
@@ -6,14 +14,53 @@ use crate::gpio::{
 use crate::hal::adc::{Channel, OneShot};
 use crate::target_device::gclk::genctrl::SRC_A::DFLL;
 use crate::target_device::gclk::pchctrl::GEN_A;
-use crate::target_device::{adc0, ADC0, ADC1, MCLK};
+use crate::target_device::{adc0, ADC0, ADC1, MCLK, SUPC};
+use crate::target_device::adc0::ctrlb::WINMODE_A;
+use crate::events;
+use core::marker::PhantomData;
 
 pub struct Adc<ADC> {
     adc: ADC,
 }
 
+/// The condition the window monitor checks each result against, the bounds
+/// are exclusive
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Window {
+    /// The result is above the lower bound
+    Above(u16),
+    /// The result is below the upper bound
+    Below(u16),
+    /// The result is between the lower and upper bound
+    Inside(u16, u16),
+    /// The result is not between the lower and upper bound
+    Outside(u16, u16),
+}
+
+/// How conversions are started while streaming
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StreamStart {
+    /// Convert back to back as fast as the ADC clock allows
+    FreeRunning,
+    /// Convert once per event on the start conversion event input, for
+    /// example a timer overflow routed through the event system
+    Event,
+}
+
+/// The result register of an ADC as a DMA source
+pub struct AdcResult<ADC> {
+    _adc: PhantomData<ADC>,
+}
+
+/// An ADC sampling continuously into a `DoubleBuffer`, as returned by
+/// `Adc::stream`.
+pub struct AdcStream<ADC, Ch, S: 'static> {
+    adc: Adc<ADC>,
+    transfer: CircularTransfer<Ch, AdcResult<ADC>, S>,
+}
+
 macro_rules! adc_hal {
-    ($($ADC:ident: ($init:ident, $mclk:ident, $apmask:ident),)+) => {
+    ($($ADC:ident: ($init:ident, $mclk:ident, $apmask:ident, $trigger:ident, $winmon:ident, [$biascomp:ident, $biasrefbuf:ident, $biasr2r:ident]),)+) => {
         $(
 impl Adc<$ADC> {
     pub fn $init(adc: $ADC, mclk: &mut MCLK, clocks: &mut GenericClockController, gclk:GEN_A) -> Self {
@@ -22,6 +69,11 @@ impl Adc<$ADC> {
         let adc_clock = clocks.configure_gclk_divider_and_source(gclk, 1, DFLL, false)
             .expect("adc clock setup failed");
         clocks.$init(&adc_clock).expect("adc clock setup failed");
+        adc.calib.write(|w| unsafe {
+            w.biascomp().bits(calibration::$biascomp());
+            w.biasrefbuf().bits(calibration::$biasrefbuf());
+            w.biasr2r().bits(calibration::$biasr2r())
+        });
         adc.ctrla.modify(|_, w| w.prescaler().div32());
         adc.ctrlb.modify(|_, w| w.ressel()._12bit());
         while adc.syncbusy.read().ctrlb().bit_is_set() {}
@@ -54,6 +106,135 @@ impl Adc<$ADC> {
         while self.adc.syncbusy.read().refctrl().bit_is_set() {}
     }
 
+    /// Compare every result against `window`, or stop comparing when
+    /// `None`. The window interrupt and event fire on each result that
+    /// matches.
+    pub fn window(&mut self, window: Option<Window>) {
+        let (mode, lower, upper) = match window {
+            None => (WINMODE_A::DISABLE, 0, 0),
+            Some(Window::Above(lower)) => (WINMODE_A::MODE1, lower, 0),
+            Some(Window::Below(upper)) => (WINMODE_A::MODE2, 0, upper),
+            Some(Window::Inside(lower, upper)) => (WINMODE_A::MODE3, lower, upper),
+            Some(Window::Outside(lower, upper)) => (WINMODE_A::MODE4, lower, upper),
+        };
+
+        self.adc.winlt.write(|w| unsafe { w.winlt().bits(lower) });
+        while self.adc.syncbusy.read().winlt().bit_is_set() {}
+        self.adc.winut.write(|w| unsafe { w.winut().bits(upper) });
+        while self.adc.syncbusy.read().winut().bit_is_set() {}
+        self.adc.ctrlb.modify(|_, w| w.winmode().variant(mode));
+        while self.adc.syncbusy.read().ctrlb().bit_is_set() {}
+    }
+
+    pub fn enable_window_interrupt(&mut self) {
+        self.adc.intenset.write(|w| w.winmon().set_bit());
+    }
+
+    pub fn disable_window_interrupt(&mut self) {
+        self.adc.intenclr.write(|w| w.winmon().set_bit());
+    }
+
+    /// Whether a result has matched the window since the flag was cleared.
+    pub fn is_window_interrupt_set(&self) -> bool {
+        self.adc.intflag.read().winmon().bit_is_set()
+    }
+
+    pub fn clear_window_interrupt(&mut self) {
+        self.adc.intflag.write(|w| w.winmon().set_bit());
+    }
+
+    /// Enable the window monitor event output, returning the generator to
+    /// publish on an event channel.
+    pub fn window_event(&mut self) -> events::GeneratorHandle<$ADC> {
+        // evctrl can only be written while the adc is disabled
+        let enabled = self.adc.ctrla.read().enable().bit_is_set();
+        self.power_down();
+        self.adc.evctrl.modify(|_, w| w.winmoneo().set_bit());
+        if enabled {
+            self.power_up();
+        }
+
+        events::GeneratorHandle::new(events::Generator::$winmon)
+    }
+
+    /// Convert `pin` back to back until `stop_free_running`, for the window
+    /// monitor to watch without the CPU.
+    pub fn start_free_running<PIN>(&mut self, _pin: &mut PIN)
+    where
+        PIN: Channel<$ADC, ID=u8>,
+    {
+        let chan = PIN::channel();
+        while self.adc.syncbusy.read().inputctrl().bit_is_set() {}
+        self.adc.inputctrl.modify(|_, w| w.muxpos().bits(chan));
+
+        self.adc.ctrlb.modify(|_, w| w.freerun().set_bit());
+        while self.adc.syncbusy.read().ctrlb().bit_is_set() {}
+
+        self.power_up();
+        self.adc.swtrig.modify(|_, w| w.start().set_bit());
+    }
+
+    pub fn stop_free_running(&mut self) {
+        self.power_down();
+        self.adc.ctrlb.modify(|_, w| w.freerun().clear_bit());
+        while self.adc.syncbusy.read().ctrlb().bit_is_set() {}
+        self.adc.intflag.write(|w| w.resrdy().set_bit());
+    }
+
+    /// Measure the die temperature, in degrees Celsius, from the PTAT and
+    /// CTAT sensors corrected with the temperature log row. The ADC settings
+    /// and the SUPC voltage reference are restored afterwards.
+    pub fn read_temperature_celsius(&mut self, supc: &mut SUPC) -> f32 {
+        let vref = supc.vref.read().bits();
+        supc.vref.modify(|_, w| {
+            w.sel()._1v0();
+            w.tsen().set_bit();
+            w.ondemand().set_bit()
+        });
+
+        let refctrl = self.adc.refctrl.read().bits();
+        let ctrlb = self.adc.ctrlb.read().bits();
+        let avgctrl = self.adc.avgctrl.read().bits();
+
+        // The log was taken against the 1V internal reference at 12 bits
+        self.reference(adc0::refctrl::REFSEL_A::INTREF);
+        self.adc.ctrlb.modify(|_, w| w.ressel()._12bit());
+        while self.adc.syncbusy.read().ctrlb().bit_is_set() {}
+        self.samples(adc0::avgctrl::SAMPLENUM_A::_1);
+
+        // The first conversion after the reference changes is discarded
+        self.convert_channel(<Ptat as Channel<$ADC>>::channel());
+        let tp = self.convert_channel(<Ptat as Channel<$ADC>>::channel()) as f32;
+        let tc = self.convert_channel(<Ctat as Channel<$ADC>>::channel()) as f32;
+
+        self.adc.refctrl.write(|w| unsafe { w.bits(refctrl) });
+        while self.adc.syncbusy.read().refctrl().bit_is_set() {}
+        self.adc.ctrlb.write(|w| unsafe { w.bits(ctrlb) });
+        while self.adc.syncbusy.read().ctrlb().bit_is_set() {}
+        self.adc.avgctrl.write(|w| unsafe { w.bits(avgctrl) });
+        while self.adc.syncbusy.read().avgctrl().bit_is_set() {}
+        supc.vref.write(|w| unsafe { w.bits(vref) });
+
+        // See 45.6.3.1 Device Temperature Measurement
+        let log = calibration::temperature_log();
+        let (tl, th) = (log.low_temp, log.high_temp);
+        let (vpl, vph) = (log.ptat_low as f32, log.ptat_high as f32);
+        let (vcl, vch) = (log.ctat_low as f32, log.ctat_high as f32);
+
+        (tl * vph * tc - vpl * th * tc - tl * vch * tp + th * vcl * tp)
+            / (vcl * tp - vch * tp - vpl * tc + vph * tc)
+    }
+
+    fn convert_channel(&mut self, chan: u8) -> u16 {
+        while self.adc.syncbusy.read().inputctrl().bit_is_set() {}
+        self.adc.inputctrl.modify(|_, w| w.muxpos().bits(chan));
+        self.power_up();
+        let result = self.convert();
+        self.power_down();
+        self.adc.intflag.write(|w| w.resrdy().set_bit());
+        result
+    }
+
     fn power_up(&mut self) {
         while self.adc.syncbusy.read().enable().bit_is_set() {}
         self.adc.ctrla.modify(|_, w| w.enable().set_bit());
@@ -75,6 +256,106 @@ impl Adc<$ADC> {
         let result = self.adc.result.read().result().bits();
         result
     }
+
+    /// Sample `pin` continuously into `buffer` using DMA `channel`, filling
+    /// each half in turn. Conversions are started as given by `start`.
+    pub fn stream<Ch, S, PIN>(
+        mut self,
+        _pin: &mut PIN,
+        start: StreamStart,
+        channel: Ch,
+        buffer: &'static mut DoubleBuffer<S>,
+        settings: Settings,
+        interrupts: Interrupts,
+    ) -> AdcStream<$ADC, Ch, S>
+    where
+        Ch: dmac::Channel,
+        S: AsRef<[u16]> + AsMut<[u16]>,
+        PIN: Channel<$ADC, ID=u8>,
+    {
+        let chan = PIN::channel();
+        while self.adc.syncbusy.read().inputctrl().bit_is_set() {}
+        self.adc.inputctrl.modify(|_, w| w.muxpos().bits(chan));
+
+        self.adc.ctrlb.modify(|_, w| w.freerun().bit(start == StreamStart::FreeRunning));
+        while self.adc.syncbusy.read().ctrlb().bit_is_set() {}
+        // evctrl can only be written while the adc is disabled
+        self.adc.evctrl.modify(|_, w| w.startei().bit(start == StreamStart::Event));
+
+        // Drop any result left over so the first beat is a fresh sample
+        self.adc.intflag.write(|w| w.resrdy().set_bit());
+
+        let transfer = CircularTransfer::start(
+            channel,
+            AdcResult { _adc: PhantomData },
+            buffer,
+            TriggerSource::$trigger,
+            settings,
+            interrupts,
+        );
+
+        self.power_up();
+        if start == StreamStart::FreeRunning {
+            self.adc.swtrig.modify(|_, w| w.start().set_bit());
+        }
+
+        AdcStream {
+            adc: self,
+            transfer,
+        }
+    }
+}
+
+unsafe impl ReadBuffer for AdcResult<$ADC> {
+    type Beat = u16;
+
+    fn dma_ptr(&mut self) -> *const u16 {
+        let adc = unsafe { &*$ADC::ptr() };
+        &adc.result as *const _ as *const u16
+    }
+
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
+
+impl<Ch, S> AdcStream<$ADC, Ch, S>
+where
+    Ch: dmac::Channel,
+    S: AsRef<[u16]> + AsMut<[u16]>,
+{
+    /// The half that was most recently filled, or `WouldBlock` if none has
+    /// filled since the last call. Read it with `read_half` before the other
+    /// half fills. Returns `Overrun` if a half was overwritten before it was
+    /// reported.
+    pub fn poll(&mut self) -> nb::Result<Half, Overrun> {
+        self.transfer.poll()
+    }
+
+    /// Copy the samples in one half of the buffer into `dest`, which must be
+    /// as long as a half. Returns `Overrun` if the half was being overwritten
+    /// during the copy.
+    pub fn read_half(&self, half: Half, dest: &mut [u16]) -> Result<(), Overrun> {
+        self.transfer.read_half(half, dest)
+    }
+
+    /// Stop sampling, then release the ADC, channel and buffer.
+    pub fn stop(self) -> (Adc<$ADC>, Ch, &'static mut DoubleBuffer<S>) {
+        let mut adc = self.adc;
+        let (channel, _, buffer) = self.transfer.stop();
+
+        adc.power_down();
+        adc.adc.ctrlb.modify(|_, w| w.freerun().clear_bit());
+        while adc.adc.syncbusy.read().ctrlb().bit_is_set() {}
+        adc.adc.evctrl.modify(|_, w| w.startei().clear_bit());
+        adc.adc.intflag.write(|w| w.resrdy().set_bit());
+
+        (adc, channel, buffer)
+    }
 }
 
 impl<WORD, PIN> OneShot<$ADC, WORD, PIN> for Adc<$ADC>
@@ -99,6 +380,281 @@ where
     }
 }
 
+/// ADC0 and ADC1 linked as master and slave, so that every conversion
+/// started on ADC0 starts one on ADC1 at the same time.
+pub struct DualAdc {
+    master: Adc<ADC0>,
+    slave: Adc<ADC1>,
+}
+
+/// The DMA sequencing register of an ADC as a DMA destination
+pub struct AdcSequence<ADC> {
+    _adc: PhantomData<ADC>,
+}
+
+/// One ADC's half of a scan: a DMA channel to load the input for each
+/// conversion, a DMA channel to store each result, the inputs built with
+/// `sequence_entry` and space for as many results.
+pub struct Scan<SeqCh, ResCh, ADC> {
+    pub sequence_channel: SeqCh,
+    pub result_channel: ResCh,
+    pub inputs: &'static [u32],
+    pub results: &'static mut [u16],
+    _adc: PhantomData<ADC>,
+}
+
+impl<SeqCh, ResCh, ADC> Scan<SeqCh, ResCh, ADC> {
+    pub fn new(sequence_channel: SeqCh, result_channel: ResCh, inputs: &'static [u32], results: &'static mut [u16]) -> Self {
+        Scan {
+            sequence_channel,
+            result_channel,
+            inputs,
+            results,
+            _adc: PhantomData,
+        }
+    }
+}
+
+/// Both ADCs working through a scan, as returned by `DualAdc::scan`.
+pub struct DualScan<SeqCh0, ResCh0, SeqCh1, ResCh1> {
+    adc: DualAdc,
+    master_sequence: Transfer<SeqCh0, &'static [u32], AdcSequence<ADC0>>,
+    master_results: Transfer<ResCh0, AdcResult<ADC0>, &'static mut [u16]>,
+    slave_sequence: Transfer<SeqCh1, &'static [u32], AdcSequence<ADC1>>,
+    slave_results: Transfer<ResCh1, AdcResult<ADC1>, &'static mut [u16]>,
+}
+
+/// The DMA sequencing word that selects `pin` as the input of a conversion,
+/// measured against ground.
+pub fn sequence_entry<ADC, PIN: Channel<ADC, ID=u8>>(_pin: &PIN) -> u32 {
+    // MUXNEG is the second byte of INPUTCTRL, 0x18 selects ground
+    PIN::channel() as u32 | (0x18 << 8)
+}
+
+impl DualAdc {
+    /// Link `slave` to `master`. Both keep their own reference, resolution
+    /// and averaging, which should normally match.
+    pub fn new(master: Adc<ADC0>, slave: Adc<ADC1>) -> Self {
+        let mut dual = DualAdc { master, slave };
+
+        dual.master.power_down();
+        dual.slave.power_down();
+        // The link can only be configured while both are disabled
+        dual.slave.adc.ctrla.modify(|_, w| w.slaveen().set_bit());
+        dual.master.adc.ctrla.modify(|_, w| w.dualsel().both());
+
+        dual
+    }
+
+    /// Convert `pin0` on ADC0 and `pin1` on ADC1 at the same time, blocking
+    /// until both results are ready.
+    pub fn read<PIN0, PIN1>(&mut self, _pin0: &mut PIN0, _pin1: &mut PIN1) -> (u16, u16)
+    where
+        PIN0: Channel<ADC0, ID=u8>,
+        PIN1: Channel<ADC1, ID=u8>,
+    {
+        let chan0 = PIN0::channel();
+        let chan1 = PIN1::channel();
+        while self.master.adc.syncbusy.read().inputctrl().bit_is_set() {}
+        self.master.adc.inputctrl.modify(|_, w| w.muxpos().bits(chan0));
+        while self.slave.adc.syncbusy.read().inputctrl().bit_is_set() {}
+        self.slave.adc.inputctrl.modify(|_, w| w.muxpos().bits(chan1));
+
+        // Enabling the master enables the slave
+        self.master.power_up();
+        // The conversion on ADC1 is started along with the one on ADC0
+        let result0 = self.master.convert();
+        while self.slave.adc.intflag.read().resrdy().bit_is_clear() {}
+        let result1 = self.slave.adc.result.read().result().bits();
+        self.master.power_down();
+
+        self.master.adc.intflag.write(|w| w.resrdy().set_bit());
+        self.slave.adc.intflag.write(|w| w.resrdy().set_bit());
+
+        (result0, result1)
+    }
+
+    /// Convert every input of `master` on ADC0 alongside the input at the
+    /// same position of `slave` on ADC1, with DMA loading each pair of
+    /// inputs and storing each pair of results. The scan starts at once and
+    /// runs until the inputs are used up.
+    ///
+    /// Panics if the scans differ in length, or there isn't space for a
+    /// result for each input.
+    pub fn scan<SeqCh0, ResCh0, SeqCh1, ResCh1>(
+        mut self,
+        master: Scan<SeqCh0, ResCh0, ADC0>,
+        slave: Scan<SeqCh1, ResCh1, ADC1>,
+        settings: Settings,
+        interrupts: Interrupts,
+    ) -> DualScan<SeqCh0, ResCh0, SeqCh1, ResCh1>
+    where
+        SeqCh0: dmac::Channel,
+        ResCh0: dmac::Channel,
+        SeqCh1: dmac::Channel,
+        ResCh1: dmac::Channel,
+    {
+        assert!(master.inputs.len() == slave.inputs.len(), "the scans differ in length");
+        assert!(master.results.len() == master.inputs.len() && slave.results.len() == slave.inputs.len(), "a scan has space for the wrong number of results");
+
+        // Each conversion loads its INPUTCTRL from DSEQDATA, the master
+        // starts converting once its input is loaded and starts the slave
+        self.master.adc.dseqctrl.write(|w| {
+            w.inputctrl().set_bit();
+            w.autostart().set_bit()
+        });
+        self.slave.adc.dseqctrl.write(|w| w.inputctrl().set_bit());
+
+        self.master.adc.intflag.write(|w| w.resrdy().set_bit());
+        self.slave.adc.intflag.write(|w| w.resrdy().set_bit());
+
+        let master_results = Transfer::start(master.result_channel, AdcResult { _adc: PhantomData }, master.results, TriggerSource::Adc0ResultReady, settings, interrupts);
+        let slave_results = Transfer::start(slave.result_channel, AdcResult { _adc: PhantomData }, slave.results, TriggerSource::Adc1ResultReady, settings, Interrupts::default());
+        let slave_sequence = Transfer::start(slave.sequence_channel, slave.inputs, AdcSequence { _adc: PhantomData }, TriggerSource::Adc1Sequence, settings, Interrupts::default());
+        let master_sequence = Transfer::start(master.sequence_channel, master.inputs, AdcSequence { _adc: PhantomData }, TriggerSource::Adc0Sequence, settings, Interrupts::default());
+
+        self.master.power_up();
+
+        DualScan {
+            adc: self,
+            master_sequence,
+            master_results,
+            slave_sequence,
+            slave_results,
+        }
+    }
+
+    /// Unlink the ADCs and release them.
+    pub fn free(mut self) -> (Adc<ADC0>, Adc<ADC1>) {
+        self.master.power_down();
+        self.slave.adc.ctrla.modify(|_, w| w.slaveen().clear_bit());
+
+        (self.master, self.slave)
+    }
+}
+
+impl<SeqCh0, ResCh0, SeqCh1, ResCh1> DualScan<SeqCh0, ResCh0, SeqCh1, ResCh1>
+where
+    SeqCh0: dmac::Channel,
+    ResCh0: dmac::Channel,
+    SeqCh1: dmac::Channel,
+    ResCh1: dmac::Channel,
+{
+    /// Whether every result of both ADCs has been stored.
+    pub fn is_complete(&self) -> bool {
+        self.master_results.is_complete() && self.slave_results.is_complete()
+    }
+
+    /// Whether the DMAC stopped any part of the scan with an error, the scan
+    /// will not complete.
+    pub fn is_failed(&self) -> bool {
+        self.master_sequence.is_failed()
+            || self.master_results.is_failed()
+            || self.slave_sequence.is_failed()
+            || self.slave_results.is_failed()
+    }
+
+    /// Block until the scan is complete, then release the ADCs and both
+    /// halves of the scan. If the DMAC stopped with an error the results
+    /// have been partially written.
+    pub fn wait(self) -> Result<
+        (DualAdc, Scan<SeqCh0, ResCh0, ADC0>, Scan<SeqCh1, ResCh1, ADC1>),
+        (dmac::transfer::Error, DualAdc, Scan<SeqCh0, ResCh0, ADC0>, Scan<SeqCh1, ResCh1, ADC1>),
+    > {
+        while !self.is_complete() && !self.is_failed() {}
+
+        let failed = self.is_failed();
+        let (adc, master, slave) = self.stop();
+        if failed {
+            Err((dmac::transfer::Error::Transfer, adc, master, slave))
+        } else {
+            Ok((adc, master, slave))
+        }
+    }
+
+    /// Abort the scan, then release the ADCs and both halves of the scan.
+    /// The results may have been partially written.
+    pub fn stop(self) -> (DualAdc, Scan<SeqCh0, ResCh0, ADC0>, Scan<SeqCh1, ResCh1, ADC1>) {
+        let mut adc = self.adc;
+        adc.master.power_down();
+        adc.master.adc.dseqctrl.reset();
+        adc.slave.adc.dseqctrl.reset();
+
+        let (seq0, inputs0, _) = self.master_sequence.stop();
+        let (res0, _, results0) = self.master_results.stop();
+        let (seq1, inputs1, _) = self.slave_sequence.stop();
+        let (res1, _, results1) = self.slave_results.stop();
+
+        (adc, Scan::new(seq0, res0, inputs0, results0), Scan::new(seq1, res1, inputs1, results1))
+    }
+}
+
+macro_rules! adc_sequence {
+    ($($ADC:ident,)+) => {
+        $(
+unsafe impl WriteBuffer for AdcSequence<$ADC> {
+    type Beat = u32;
+
+    fn dma_ptr(&mut self) -> *mut u32 {
+        let adc = unsafe { &*$ADC::ptr() };
+        &adc.dseqdata as *const _ as *mut u32
+    }
+
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
+        )+
+    }
+}
+
+adc_sequence! {
+    ADC0,
+    ADC1,
+}
+
+macro_rules! adc_internal {
+    ($($(#[$attr:meta])* $Type:ident: $chan:expr,)+) => {
+        $(
+$(#[$attr])*
+pub struct $Type;
+
+impl Channel<ADC0> for $Type {
+   type ID = u8;
+   fn channel() -> u8 { $chan }
+}
+
+impl Channel<ADC1> for $Type {
+   type ID = u8;
+   fn channel() -> u8 { $chan }
+}
+        )+
+    }
+}
+
+adc_internal! {
+    /// The core supply divided by 4
+    ScaledCoreSupply: 0x18,
+    /// The battery backup supply divided by 4
+    ScaledVbat: 0x19,
+    /// The I/O supply divided by 4
+    ScaledIoSupply: 0x1a,
+    /// The bandgap reference, enabled with VREFOE in the SUPC VREF register
+    Bandgap: 0x1b,
+    /// The temperature sensor proportional to absolute temperature, enabled
+    /// with TSEN in the SUPC VREF register
+    Ptat: 0x1c,
+    /// The temperature sensor complementary to absolute temperature, enabled
+    /// with TSEN in the SUPC VREF register
+    Ctat: 0x1d,
+    /// The DAC output
+    DacOutput: 0x1e,
+}
+
 macro_rules! adc_pins {
     ($($pin:ident: ($ADC:ident, $chan:expr),)+) => {
         $(
@@ -112,8 +668,8 @@ impl Channel<$ADC> for $pin<PfB> {
 }
 
 adc_hal! {
-    ADC0: (adc0, apbdmask, adc0_),
-    ADC1: (adc1, apbdmask, adc1_),
+    ADC0: (adc0, apbdmask, adc0_, Adc0ResultReady, ADC0_WINMON, [adc0_biascomp_cal, adc0_biasrefbuf_cal, adc0_biasr2r_cal]),
+    ADC1: (adc1, apbdmask, adc1_, Adc1ResultReady, ADC1_WINMON, [adc1_biascomp_cal, adc1_biasrefbuf_cal, adc1_biasr2r_cal]),
 }
 
 adc_pins! {
@@ -15,6 +15,48 @@ fn cal(addr_offset: u32, bit_shift: u32, bit_mask: u32) -> u32 {
     }
 }
 
+/// AC comparator bias calibration value. Should be written to the AC CALIB
+/// register.
+pub fn ac_bias_cal() -> u8 {
+    cal(0, 0, 0b11) as u8
+}
+
+/// ADC0 comparator bias calibration value. Should be written to the ADC0
+/// CALIB register.
+pub fn adc0_biascomp_cal() -> u8 {
+    cal(0, 2, 0b111) as u8
+}
+
+/// ADC0 reference buffer bias calibration value. Should be written to the
+/// ADC0 CALIB register.
+pub fn adc0_biasrefbuf_cal() -> u8 {
+    cal(0, 5, 0b111) as u8
+}
+
+/// ADC0 R2R bias calibration value. Should be written to the ADC0 CALIB
+/// register.
+pub fn adc0_biasr2r_cal() -> u8 {
+    cal(0, 8, 0b111) as u8
+}
+
+/// ADC1 comparator bias calibration value. Should be written to the ADC1
+/// CALIB register.
+pub fn adc1_biascomp_cal() -> u8 {
+    cal(0, 16, 0b111) as u8
+}
+
+/// ADC1 reference buffer bias calibration value. Should be written to the
+/// ADC1 CALIB register.
+pub fn adc1_biasrefbuf_cal() -> u8 {
+    cal(0, 19, 0b111) as u8
+}
+
+/// ADC1 R2R bias calibration value. Should be written to the ADC1 CALIB
+/// register.
+pub fn adc1_biasr2r_cal() -> u8 {
+    cal(0, 22, 0b111) as u8
+}
+
 /// USB TRANSN calibration value. Should be written to USB PADCAL register.
 pub fn usb_transn_cal() -> u8 {
     cal(4, 0, 0b11111) as u8
@@ -29,3 +71,40 @@ pub fn usb_transp_cal() -> u8 {
 pub fn usb_trim_cal() -> u8 {
     cal(4, 10, 0b111) as u8
 }
+
+/// The temperature log row, the ADC readings of the PTAT and CTAT
+/// temperature sensors taken at two temperatures during production. See 9.6
+/// NVM Temperature Log Row.
+#[derive(Clone, Copy, Debug)]
+pub struct TemperatureLog {
+    /// The low temperature in degrees Celsius
+    pub low_temp: f32,
+    /// The high temperature in degrees Celsius
+    pub high_temp: f32,
+    /// The 12 bit ADC reading of PTAT at the low temperature
+    pub ptat_low: u16,
+    /// The 12 bit ADC reading of PTAT at the high temperature
+    pub ptat_high: u16,
+    /// The 12 bit ADC reading of CTAT at the low temperature
+    pub ctat_low: u16,
+    /// The 12 bit ADC reading of CTAT at the high temperature
+    pub ctat_high: u16,
+}
+
+// "The NVM Temperature Log Row can be read at address 0x00800100."
+const TEMP_LOG_OFFSET: u32 = 0x00800100 - ADDR;
+
+/// Returns the temperature log row.
+pub fn temperature_log() -> TemperatureLog {
+    let temp = |int, dec| int as f32 + dec as f32 / 10.0;
+    let log = |offset, bit_shift, bit_mask| cal(TEMP_LOG_OFFSET + offset, bit_shift, bit_mask);
+
+    TemperatureLog {
+        low_temp: temp(log(0, 0, 0xff), log(0, 8, 0xf)),
+        high_temp: temp(log(0, 12, 0xff), log(0, 20, 0xf)),
+        ptat_low: log(4, 8, 0xfff) as u16,
+        ptat_high: log(4, 20, 0xfff) as u16,
+        ctat_low: log(8, 0, 0xfff) as u16,
+        ctat_high: log(8, 12, 0xfff) as u16,
+    }
+}
@@ -367,6 +367,19 @@ impl GenericClockController {
 }
 
 clock_generator!(
+    (eic, EicClock, EIC),
+    (evch0, Evch0Clock, EVSYS0),
+    (evch1, Evch1Clock, EVSYS1),
+    (evch2, Evch2Clock, EVSYS2),
+    (evch3, Evch3Clock, EVSYS3),
+    (evch4, Evch4Clock, EVSYS4),
+    (evch5, Evch5Clock, EVSYS5),
+    (evch6, Evch6Clock, EVSYS6),
+    (evch7, Evch7Clock, EVSYS7),
+    (evch8, Evch8Clock, EVSYS8),
+    (evch9, Evch9Clock, EVSYS9),
+    (evch10, Evch10Clock, EVSYS10),
+    (evch11, Evch11Clock, EVSYS11),
     (tc0_tc1, Tc0Tc1Clock, TC0_TC1),
     (tcc0_tcc1, Tcc0Tcc1Clock, TCC0_TCC1),
     (tc2_tc3, Tc2Tc3Clock, TC2_TC3),
@@ -374,6 +387,7 @@ clock_generator!(
     (tc4_tc5, Tc4Tc5Clock, TC4_TC5),
     (tcc4, Tcc4Clock, TCC4),
     (tc6_tc7, Tc6Tc7Clock, TC6_TC7),
+    (pdec, PdecClock, PDEC),
     (sercom0_core, Sercom0CoreClock, SERCOM0_CORE),
     (sercom1_core, Sercom1CoreClock, SERCOM1_CORE),
     (sercom2_core, Sercom2CoreClock, SERCOM2_CORE),
@@ -383,6 +397,8 @@ clock_generator!(
     (usb, UsbClock, USB),
     (adc0, Adc0Clock, ADC0),
     (adc1, Adc1Clock, ADC1),
+    (dac, DacClock, DAC),
+    (ac, AcClock, AC),
 );
 
 /// The frequency of the 48Mhz source.
@@ -0,0 +1,343 @@
+use crate::{
+    target_device,
+    events,
+    gpio::{
+        self,
+        Port,
+        IntoFunction,
+    },
+};
+
+/// The EicPin trait makes it more ergonomic to convert a gpio pin into an EIC
+/// pin. You should not implement this trait for yourself; only the
+/// implementations in the EIC module make sense.
+pub trait EicPin<T> {
+    fn into_ei(self, port: &mut Port) -> T;
+}
+
+pub type Sense = target_device::eic::config::SENSE0_A;
+
+pub type NmiSense = target_device::eic::nmictrl::NMISENSE_A;
+
+/// The non-maskable interrupt pin. Its interrupt is taken by the
+/// `NonMaskableInt` exception handler, which runs even while interrupts are
+/// masked.
+pub struct NmiPin<GPIO>(GPIO);
+
+impl<GPIO> NmiPin<GPIO> {
+    /// Construct the NMI from the appropriate pin in any mode.
+    /// You may find it more convenient to use the `into_ei` trait
+    /// and avoid referencing the pin type.
+    pub fn new(pin: GPIO) -> Self {
+        NmiPin(pin)
+    }
+
+    pub fn sense(&mut self, eic: &mut super::EIC, sense: NmiSense) {
+        eic.eic.nmictrl.modify(|_, w| w.nmisense().variant(sense));
+    }
+
+    pub fn filter(&mut self, eic: &mut super::EIC, filter: bool) {
+        eic.eic.nmictrl.modify(|_, w| w.nmifilten().bit(filter));
+    }
+
+    /// Detect the sense condition asynchronously, without the EIC clock, so
+    /// the NMI works in sleep modes that stop it. Can't be combined with
+    /// filtering.
+    pub fn asynchronous(&mut self, eic: &mut super::EIC, asynchronous: bool) {
+        eic.eic.nmictrl.modify(|_, w| w.nmiasynch().bit(asynchronous));
+    }
+
+    pub fn is_interrupt(&mut self) -> bool {
+        nmi_is_interrupt()
+    }
+
+    pub fn clear_interrupt(&mut self) {
+        nmi_clear_interrupt()
+    }
+}
+
+/// Whether the NMI pin has detected its sense condition. The `NmiPin` can't
+/// be shared safely with the `NonMaskableInt` handler, so this is available
+/// without it.
+pub fn nmi_is_interrupt() -> bool {
+    unsafe { &(*target_device::EIC::ptr()) }.nmiflag.read().nmi().bit_is_set()
+}
+
+/// Clear the NMI flag, call this from the `NonMaskableInt` handler.
+pub fn nmi_clear_interrupt() {
+    unsafe { &(*target_device::EIC::ptr()) }.nmiflag.write(|w| w.nmi().set_bit());
+}
+
+impl<MODE> EicPin<NmiPin<gpio::Pa8<gpio::PfA>>> for gpio::Pa8<MODE> {
+    fn into_ei(self, port: &mut Port) -> NmiPin<gpio::Pa8<gpio::PfA>> {
+        NmiPin::new(self.into_function(port))
+    }
+}
+
+/// The pad macro defines the given EIC pin and implements EicPin for the
+/// given pins. The EicPin implementation will configure the pin for the
+/// appropriate function and return the pin wrapped in the EIC type.
+///
+/// There is no wake up register on this family, an enabled external
+/// interrupt wakes the core from sleep.
+macro_rules! ei {
+    ($PadType:ident [ $num:expr ] {
+        $($(#[$attr:meta])* $PinType:ident ,)+
+    }
+    ) => {
+crate::paste::item! {
+    /// Represents a numbered external interrupt. The external interrupt is
+    /// generic over any pin, only the EicPin implementations in this module
+    /// make sense.
+    pub struct [<$PadType $num>]<GPIO>(GPIO);
+
+    impl<GPIO> [<$PadType $num>]<GPIO> {
+        /// Construct pad from the appropriate pin in any mode.
+        /// You may find it more convenient to use the `into_pad` trait
+        /// and avoid referencing the pad type.
+        pub fn new(pin: GPIO) -> Self {
+            [<$PadType $num>](pin)
+        }
+
+        /// Configure the eic with options for this external interrupt
+        pub fn enable_event(&mut self, eic: &mut super::EIC) {
+            eic.configure(|eic| {
+                eic.evctrl.modify(|r, w| unsafe {
+                    w.extinteo().bits(r.extinteo().bits() | (1 << $num))
+                });
+            });
+        }
+
+        /// Enable the event output for this external interrupt, returning the
+        /// generator to publish on an event channel.
+        pub fn event(&mut self, eic: &mut super::EIC) -> events::GeneratorHandle<target_device::EIC> {
+            self.enable_event(eic);
+            events::GeneratorHandle::new(events::Generator::[<EXTINT $num>])
+        }
+
+        pub fn enable_interrupt(&mut self, eic: &mut super::EIC) {
+            eic.eic.intenset.write(|w| unsafe {
+                w.extint().bits(1 << $num)
+            });
+        }
+
+        pub fn disable_interrupt(&mut self, eic: &mut super::EIC) {
+            eic.eic.intenclr.write(|w| unsafe {
+                w.extint().bits(1 << $num)
+            });
+        }
+
+        pub fn is_interrupt(&mut self) -> bool {
+            unsafe { &(*target_device::EIC::ptr()) }.intflag.read().extint().bits() & (1 << $num) != 0
+        }
+
+        pub fn clear_interrupt(&mut self) {
+            unsafe { &(*target_device::EIC::ptr()) }.intflag.write(|w| unsafe {
+                w.extint().bits(1 << $num)
+            });
+        }
+
+        pub fn sense(&mut self, eic: &mut super::EIC, sense: Sense) {
+            // Which of the two config blocks this eic config is in
+            let offset = ($num >> 3) & 0b0001;
+
+            eic.configure(|eic| {
+                eic.config[offset].modify(|_, w| unsafe {
+                    // Which of the eight eic configs in this config block
+                    match $num & 0b111 {
+                        0b000 => w.sense0().bits(sense as u8),
+                        0b001 => w.sense1().bits(sense as u8),
+                        0b010 => w.sense2().bits(sense as u8),
+                        0b011 => w.sense3().bits(sense as u8),
+                        0b100 => w.sense4().bits(sense as u8),
+                        0b101 => w.sense5().bits(sense as u8),
+                        0b110 => w.sense6().bits(sense as u8),
+                        0b111 => w.sense7().bits(sense as u8),
+                        _ => unimplemented!(),
+                    }
+                });
+            });
+        }
+
+        /// Filter the pin with a majority vote of three samples of the EIC
+        /// clock. Filtering can't be combined with debouncing.
+        pub fn filter(&mut self, eic: &mut super::EIC, filter: bool) {
+            // Which of the two config blocks this eic config is in
+            let offset = ($num >> 3) & 0b0001;
+
+            eic.configure(|eic| {
+                eic.config[offset].modify(|_, w| {
+                    // Which of the eight eic configs in this config block
+                    match $num & 0b111 {
+                        0b000 => w.filten0().bit(filter),
+                        0b001 => w.filten1().bit(filter),
+                        0b010 => w.filten2().bit(filter),
+                        0b011 => w.filten3().bit(filter),
+                        0b100 => w.filten4().bit(filter),
+                        0b101 => w.filten5().bit(filter),
+                        0b110 => w.filten6().bit(filter),
+                        0b111 => w.filten7().bit(filter),
+                        _ => unimplemented!(),
+                    }
+                });
+            });
+        }
+
+        /// Debounce the pin, see `EIC::debounce_prescaler` for the sampling
+        /// rate. Only edge sense modes can be debounced.
+        pub fn debounce(&mut self, eic: &mut super::EIC, debounce: bool) {
+            eic.configure(|eic| {
+                eic.debouncen.modify(|r, w| unsafe {
+                    if debounce {
+                        w.debouncen().bits(r.debouncen().bits() | (1 << $num))
+                    } else {
+                        w.debouncen().bits(r.debouncen().bits() & !(1 << $num))
+                    }
+                });
+            });
+        }
+
+        /// Detect edges asynchronously, without the EIC clock. This lets the
+        /// pin wake the core from sleep modes that stop the clock, but can't
+        /// be combined with filtering or debouncing.
+        pub fn asynchronous(&mut self, eic: &mut super::EIC, asynchronous: bool) {
+            eic.configure(|eic| {
+                eic.asynch.modify(|r, w| unsafe {
+                    if asynchronous {
+                        w.asynch().bits(r.asynch().bits() | (1 << $num))
+                    } else {
+                        w.asynch().bits(r.asynch().bits() & !(1 << $num))
+                    }
+                });
+            });
+        }
+
+        /// The debounced state of the pin, only updated while debouncing is
+        /// enabled for it.
+        pub fn state(&self, eic: &super::EIC) -> bool {
+            eic.pin_state() & (1 << $num) != 0
+        }
+    }
+
+    $(
+        $(
+            #[$attr]
+        )*
+        impl<MODE> EicPin<[<$PadType $num>]<gpio::$PinType<gpio::PfA>>> for gpio::$PinType<MODE> {
+            fn into_ei(self, port: &mut Port) -> [<$PadType $num>]<gpio::$PinType<gpio::PfA>> {
+                [<$PadType $num>]::new(self.into_function(port))
+            }
+        }
+    )+
+}
+
+    };
+}
+
+ei!(ExtInt[0] {
+    Pa0,
+    Pa16,
+    Pb0,
+    Pb16,
+});
+
+ei!(ExtInt[1] {
+    Pa1,
+    Pa17,
+    Pb1,
+    Pb17,
+});
+
+ei!(ExtInt[2] {
+    Pa2,
+    Pa18,
+    Pb2,
+    Pb18,
+});
+
+ei!(ExtInt[3] {
+    Pa3,
+    Pa19,
+    Pb3,
+    Pb19,
+});
+
+ei!(ExtInt[4] {
+    Pa4,
+    Pa20,
+    Pb4,
+    Pb20,
+});
+
+ei!(ExtInt[5] {
+    Pa5,
+    Pa21,
+    Pb5,
+    Pb21,
+});
+
+ei!(ExtInt[6] {
+    Pa6,
+    Pa22,
+    Pb6,
+    Pb22,
+});
+
+ei!(ExtInt[7] {
+    Pa7,
+    Pa23,
+    Pb7,
+    Pb23,
+});
+
+ei!(ExtInt[8] {
+    Pa24,
+    Pb8,
+    Pb24,
+});
+
+ei!(ExtInt[9] {
+    Pa9,
+    Pa25,
+    Pb9,
+    Pb25,
+});
+
+ei!(ExtInt[10] {
+    Pa10,
+    Pb10,
+});
+
+ei!(ExtInt[11] {
+    Pa11,
+    Pa27,
+    Pb11,
+});
+
+ei!(ExtInt[12] {
+    Pa12,
+    Pb12,
+    Pb26,
+});
+
+ei!(ExtInt[13] {
+    Pa13,
+    Pb13,
+    Pb27,
+});
+
+ei!(ExtInt[14] {
+    Pa14,
+    Pa30,
+    Pb14,
+    Pb28,
+    Pb30,
+});
+
+ei!(ExtInt[15] {
+    Pa15,
+    Pa31,
+    Pb15,
+    Pb29,
+    Pb31,
+});
@@ -0,0 +1,489 @@
+//! Position decoding with the PDEC peripheral.
+//!
+//! The PDEC counts the edges of a quadrature encoder or the states of a hall
+//! sensor in hardware, so no steps are lost at speeds where an interrupt per
+//! edge can't keep up. The inputs come from the QDI pins or from the event
+//! system.
+//!
+//! In quadrature mode the count is split into a position counter, the low
+//! bits set by `set_position_bits`, and a revolution counter in the rest. An
+//! index input on QDI2 resets the position once per revolution.
+//!
+//! ```ignore
+//! let a = pins.pa24.into_qdi(&mut pins.port);
+//! let b = pins.pa25.into_qdi(&mut pins.port);
+//!
+//! let mut pdec = Pdec::new(&pdec_clock, peripherals.PDEC, Mode::Quadrature(QuadratureConfig::X4), &mut mclk);
+//! pdec.enable_pin(&a, false);
+//! pdec.enable_pin(&b, false);
+//! pdec.set_filter(4);
+//! pdec.start();
+//!
+//! let steps = pdec.count();
+//! ```
+use crate::{
+	target_device,
+	target_device::{pdec, PDEC},
+	clock,
+	events,
+	gpio::{self, IntoFunction, Port},
+	time::Hertz,
+};
+
+/// The division of the PDEC clock that the filter and counter run at
+pub type Prescaler = pdec::presc::PRESC_A;
+
+/// How the phases of a quadrature encoder are decoded. The `X4` modes count
+/// every edge of both phases and the `X2` modes every edge of phase A. The
+/// secure `S` modes check the position against the index, and `AUTOC`
+/// corrects the position at the index.
+pub type QuadratureConfig = pdec::ctrla::CONF_A;
+
+/// What happens on event input 0 in counter mode
+pub type EventAction = pdec::evctrl::EVACT_A;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mode {
+	/// Decode phase A and B on inputs 0 and 1, with the index on input 2
+	Quadrature(QuadratureConfig),
+	/// Track a hall sensor on the three inputs
+	Hall,
+	/// Count the events chosen by the event action
+	Counter,
+}
+
+/// The inputs of the PDEC, from the QDI pins or the event system
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Input {
+	/// Phase A in quadrature mode
+	Qdi0,
+	/// Phase B in quadrature mode
+	Qdi1,
+	/// The index in quadrature mode
+	Qdi2,
+}
+
+/// A compare channel, CC0 is also the period when it is enabled
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompareChannel {
+	_0,
+	_1,
+}
+
+/// The conditions the PDEC can interrupt on and emit events for
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Flag {
+	/// The counter wrapped past the period or the end of its range
+	Overflow,
+	/// An error was detected, see `error`
+	Error,
+	/// The direction of rotation changed
+	Direction,
+	/// The velocity exceeded the limit set in CC0 (hall mode)
+	Velocity,
+	/// The counter matched CC0
+	Compare0,
+	/// The counter matched CC1
+	Compare1,
+}
+
+impl Flag {
+	fn mask(self) -> u8 {
+		1 << self as u8
+	}
+}
+
+/// An error the PDEC detected in its inputs
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Error {
+	/// Both phases changed at once, an edge was missed
+	Quadrature,
+	/// The index arrived at a position other than zero
+	Index,
+	/// More index pulses were missed than allowed
+	MissingPulse,
+	/// The hall inputs changed outside of the expected window
+	Window,
+	/// The hall inputs are in an invalid state
+	Hall,
+}
+
+/// The direction the encoder last moved in
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+	Up,
+	Down,
+}
+
+/// Converts a pin into the QDI input it can be multiplexed to. You should not
+/// implement this trait yourself, only the implementations in this module
+/// make sense.
+pub trait QdiPin<T> {
+	fn into_qdi(self, port: &mut Port) -> T;
+}
+
+/// A pin multiplexed to one of the PDEC inputs
+pub trait InputPin {
+	fn input(&self) -> Input;
+}
+
+macro_rules! qdi {
+	($($Qdi:ident: ($input:ident, [$($PinType:ident,)+]),)+) => {
+		$(
+/// A pin multiplexed to the PDEC input with the same number. The type is
+/// generic over any pin, only the `QdiPin` implementations make sense.
+pub struct $Qdi<PIN>(PIN);
+
+impl<PIN> $Qdi<PIN> {
+	/// Construct the input from a pin in any mode. You may find `into_qdi`
+	/// more convenient.
+	pub fn new(pin: PIN) -> Self {
+		$Qdi(pin)
+	}
+
+	/// Release the pin.
+	pub fn free(self) -> PIN {
+		self.0
+	}
+}
+
+impl<PIN> InputPin for $Qdi<PIN> {
+	fn input(&self) -> Input {
+		Input::$input
+	}
+}
+
+$(
+impl<MODE> QdiPin<$Qdi<gpio::$PinType<gpio::PfG>>> for gpio::$PinType<MODE> {
+	fn into_qdi(self, port: &mut Port) -> $Qdi<gpio::$PinType<gpio::PfG>> {
+		$Qdi::new(self.into_function(port))
+	}
+}
+)+
+		)+
+	}
+}
+
+qdi! {
+	Qdi0: (Qdi0, [Pa24, Pb18, Pb22,]),
+	Qdi1: (Qdi1, [Pa25, Pb19, Pb23,]),
+	Qdi2: (Qdi2, [Pb20,]),
+}
+
+/// A position decoder.
+pub struct Pdec {
+	pdec: PDEC,
+	freq: Hertz,
+	position_bits: u8,
+}
+
+impl Pdec {
+	/// Configure the PDEC in `mode`, with no inputs enabled. The counter
+	/// doesn't run until `start` is called.
+	pub fn new(clock: &clock::PdecClock, pdec: PDEC, mode: Mode, mclk: &mut target_device::MCLK) -> Self {
+		mclk.apbcmask.modify(|_, w| w.pdec_().set_bit());
+
+		pdec.ctrla.modify(|_, w| w.enable().clear_bit());
+		while pdec.syncbusy.read().enable().bit_is_set() {}
+		pdec.ctrla.write(|w| w.swrst().set_bit());
+		while pdec.syncbusy.read().swrst().bit_is_set() {}
+
+		pdec.ctrla.write(|w| unsafe {
+			match mode {
+				Mode::Quadrature(config) => {
+					w.mode().qdec();
+					w.conf().variant(config);
+				},
+				Mode::Hall => {
+					w.mode().hall();
+				},
+				Mode::Counter => {
+					w.mode().counter();
+				},
+			}
+			// The position counter takes all 16 bits until told otherwise
+			w.angular().bits(16 - 9)
+		});
+
+		pdec.ctrla.modify(|_, w| w.enable().set_bit());
+		while pdec.syncbusy.read().enable().bit_is_set() {}
+
+		Self {
+			pdec,
+			freq: clock.freq(),
+			position_bits: 16,
+		}
+	}
+
+	/// The frequency the filter and counter run at.
+	pub fn frequency(&self) -> Hertz {
+		self.freq
+	}
+
+	/// Take inputs from the pin.
+	pub fn enable_pin<P: InputPin>(&mut self, pin: &P, inverted: bool) {
+		let n = pin.input() as u32;
+		self.configure(|pdec| {
+			pdec.ctrla.modify(|r, w| unsafe {
+				// PINENn are bits 16 to 18, PINVENn are bits 20 to 22
+				let bits = r.bits() & !(0x0011_0000 << n);
+				w.bits(bits | (1 << (16 + n)) | ((inverted as u32) << (20 + n)))
+			});
+		});
+	}
+
+	/// Stop taking inputs from the pin for `input`.
+	pub fn disable_pin(&mut self, input: Input) {
+		let n = input as u32;
+		self.configure(|pdec| {
+			pdec.ctrla.modify(|r, w| unsafe {
+				w.bits(r.bits() & !(0x0011_0000 << n))
+			});
+		});
+	}
+
+	/// Take `input` from the event system instead of a pin, returning the
+	/// user to subscribe to the generating channel.
+	pub fn event_input(&mut self, input: Input, inverted: bool) -> events::UserHandle<PDEC> {
+		let n = input as u8;
+		self.configure(|pdec| {
+			pdec.evctrl.modify(|r, w| unsafe {
+				w.evei().bits(r.evei().bits() | (1 << n));
+				if inverted {
+					w.evinv().bits(r.evinv().bits() | (1 << n))
+				} else {
+					w.evinv().bits(r.evinv().bits() & !(1 << n))
+				}
+			});
+		});
+
+		events::UserHandle::new(match input {
+			Input::Qdi0 => events::User::PDEC_EVU0,
+			Input::Qdi1 => events::User::PDEC_EVU1,
+			Input::Qdi2 => events::User::PDEC_EVU2,
+		})
+	}
+
+	/// Choose what event input 0 does, counting in counter mode or
+	/// restarting the counter.
+	pub fn set_event_action(&mut self, action: EventAction) {
+		self.configure(|pdec| {
+			pdec.evctrl.modify(|_, w| w.evact().variant(action));
+		});
+	}
+
+	/// Swap phase A and B, reversing the direction of the count.
+	pub fn swap(&mut self, swap: bool) {
+		self.configure(|pdec| {
+			pdec.ctrla.modify(|_, w| w.swap().bit(swap));
+		});
+	}
+
+	/// Set the number of bits of the count that hold the position, between 9
+	/// and 16. The bits above hold the revolution counter.
+	///
+	/// Panics if `bits` is out of range.
+	pub fn set_position_bits(&mut self, bits: u8) {
+		assert!(bits >= 9 && bits <= 16, "the position counter is 9 to 16 bits");
+
+		self.configure(|pdec| {
+			pdec.ctrla.modify(|_, w| unsafe { w.angular().bits(bits - 9) });
+		});
+		self.position_bits = bits;
+	}
+
+	/// Set how many index pulses can be missed in a row before a missing
+	/// pulse error, from 0 to 15.
+	pub fn set_max_missing_pulses(&mut self, pulses: u8) {
+		self.configure(|pdec| {
+			pdec.ctrla.modify(|_, w| unsafe { w.maxcmp().bits(pulses.min(15)) });
+		});
+	}
+
+	/// Set the division of the PDEC clock for the filter and counter.
+	pub fn set_prescaler(&mut self, prescaler: Prescaler, clock: &clock::PdecClock) {
+		self.pdec.presc.write(|w| w.presc().variant(prescaler));
+		while self.pdec.syncbusy.read().presc().bit_is_set() {}
+
+		self.freq = Hertz(clock.freq().0 >> u8::from(prescaler));
+	}
+
+	/// Ignore pulses on the inputs shorter than `length` prescaled clock
+	/// cycles, zero disables the filter.
+	pub fn set_filter(&mut self, length: u8) {
+		self.pdec.filter.write(|w| unsafe { w.filter().bits(length) });
+		while self.pdec.syncbusy.read().filter().bit_is_set() {}
+	}
+
+	/// Wrap the count at `period`, or at the end of its range when `None`.
+	/// In quadrature mode the period is split like the count, the position
+	/// bits give the steps per revolution and the bits above the number of
+	/// revolutions.
+	pub fn set_period(&mut self, period: Option<u16>) {
+		if let Some(period) = period {
+			self.set_compare(CompareChannel::_0, period);
+		}
+		self.configure(|pdec| {
+			pdec.ctrla.modify(|_, w| w.peren().bit(period.is_some()));
+		});
+	}
+
+	/// Set the value the count is compared against on `channel`.
+	pub fn set_compare(&mut self, channel: CompareChannel, value: u16) {
+		self.pdec.cc[channel as usize].write(|w| unsafe { w.cc().bits(value) });
+		while self.pdec.syncbusy.read().bits() & (0x80 << channel as u8) != 0 {}
+	}
+
+	/// Start counting in quadrature and hall mode.
+	pub fn start(&mut self) {
+		self.command(|w| w.cmd().start());
+	}
+
+	/// Stop counting in quadrature and hall mode, keeping the count.
+	pub fn stop(&mut self) {
+		self.command(|w| w.cmd().stop());
+	}
+
+	pub fn is_running(&self) -> bool {
+		self.pdec.status.read().stop().bit_is_clear()
+	}
+
+	/// Restart the count from zero.
+	pub fn retrigger(&mut self) {
+		self.command(|w| w.cmd().retrigger());
+	}
+
+	/// The whole count, the revolutions and the position together in
+	/// quadrature mode.
+	pub fn count(&mut self) -> u16 {
+		self.command(|w| w.cmd().readsync());
+		while self.pdec.syncbusy.read().count().bit_is_set() {}
+
+		self.pdec.count.read().count().bits()
+	}
+
+	/// Overwrite the count.
+	pub fn set_count(&mut self, count: u16) {
+		self.pdec.count.write(|w| unsafe { w.count().bits(count) });
+		while self.pdec.syncbusy.read().count().bit_is_set() {}
+	}
+
+	/// The position within the current revolution.
+	pub fn position(&mut self) -> u16 {
+		let mask = (0xffff_u32 >> (16 - self.position_bits)) as u16;
+		self.count() & mask
+	}
+
+	/// The number of revolutions, wrapping at the width of the revolution
+	/// counter.
+	pub fn revolutions(&mut self) -> u16 {
+		((self.count() as u32) >> self.position_bits) as u16
+	}
+
+	pub fn direction(&self) -> Direction {
+		if self.pdec.status.read().dir().bit_is_set() {
+			Direction::Down
+		} else {
+			Direction::Up
+		}
+	}
+
+	/// The first error flagged since the errors were last cleared.
+	pub fn error(&self) -> Option<Error> {
+		let status = self.pdec.status.read();
+		if status.qerr().bit_is_set() {
+			Some(Error::Quadrature)
+		} else if status.idxerr().bit_is_set() {
+			Some(Error::Index)
+		} else if status.mperr().bit_is_set() {
+			Some(Error::MissingPulse)
+		} else if status.winerr().bit_is_set() {
+			Some(Error::Window)
+		} else if status.herr().bit_is_set() {
+			Some(Error::Hall)
+		} else {
+			None
+		}
+	}
+
+	pub fn clear_errors(&mut self) {
+		self.pdec.status.write(|w| {
+			w.qerr().set_bit();
+			w.idxerr().set_bit();
+			w.mperr().set_bit();
+			w.winerr().set_bit();
+			w.herr().set_bit()
+		});
+		while self.pdec.syncbusy.read().status().bit_is_set() {}
+	}
+
+	pub fn enable_interrupt(&mut self, flag: Flag) {
+		self.pdec.intenset.write(|w| unsafe { w.bits(flag.mask()) });
+	}
+
+	pub fn disable_interrupt(&mut self, flag: Flag) {
+		self.pdec.intenclr.write(|w| unsafe { w.bits(flag.mask()) });
+	}
+
+	pub fn is_set(&self, flag: Flag) -> bool {
+		self.pdec.intflag.read().bits() & flag.mask() != 0
+	}
+
+	pub fn clear(&mut self, flag: Flag) {
+		self.pdec.intflag.write(|w| unsafe { w.bits(flag.mask()) });
+	}
+
+	/// Emit an event on `flag`, returning the generator to publish on an
+	/// event channel.
+	pub fn event(&mut self, flag: Flag) -> events::GeneratorHandle<PDEC> {
+		self.configure(|pdec| {
+			pdec.evctrl.modify(|r, w| unsafe {
+				// The output event enables are bits 8 to 13, in flag order
+				w.bits(r.bits() | ((flag.mask() as u16) << 8))
+			});
+		});
+
+		events::GeneratorHandle::new(match flag {
+			Flag::Overflow => events::Generator::PDEC_OVF,
+			Flag::Error => events::Generator::PDEC_ERR,
+			Flag::Direction => events::Generator::PDEC_DIR,
+			Flag::Velocity => events::Generator::PDEC_VLC,
+			Flag::Compare0 => events::Generator::PDEC_MC0,
+			Flag::Compare1 => events::Generator::PDEC_MC1,
+		})
+	}
+
+	/// Disable the PDEC and release it.
+	pub fn free(self) -> PDEC {
+		self.pdec.ctrla.modify(|_, w| w.enable().clear_bit());
+		while self.pdec.syncbusy.read().enable().bit_is_set() {}
+
+		self.pdec
+	}
+
+	fn command<F>(&mut self, f: F)
+	where
+		F: FnOnce(&mut pdec::ctrlbset::W) -> &mut pdec::ctrlbset::W,
+	{
+		self.pdec.ctrlbset.write(f);
+		while self.pdec.syncbusy.read().ctrlb().bit_is_set() {}
+	}
+
+	/// CTRLA and EVCTRL can only be written while the PDEC is disabled, it
+	/// is enabled and started again afterwards if it was running.
+	fn configure<F: FnOnce(&PDEC)>(&mut self, f: F) {
+		let running = self.is_running();
+
+		self.pdec.ctrla.modify(|_, w| w.enable().clear_bit());
+		while self.pdec.syncbusy.read().enable().bit_is_set() {}
+
+		f(&self.pdec);
+
+		self.pdec.ctrla.modify(|_, w| w.enable().set_bit());
+		while self.pdec.syncbusy.read().enable().bit_is_set() {}
+
+		if running {
+			self.start();
+		}
+	}
+}
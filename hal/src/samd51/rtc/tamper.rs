@@ -0,0 +1,237 @@
+//! Backup registers and tamper detection.
+//!
+//! The eight backup registers and four general purpose registers keep their
+//! value through backup sleep and resets other than power on. The tamper
+//! inputs IN0 to IN4 can wake the core, capture the count or clock into the
+//! timestamp register, and erase the backup and general purpose registers
+//! when they see their edge. The tamper pins must be
+//! configured as inputs in the PORT.
+use crate::target_device;
+
+use super::{ClockMode, Count16Mode, Count32Mode, DateTime, BASE_YEAR, RTC};
+
+/// What a tamper input does when it detects its edge. `ACTL` compares the
+/// input against the active layer output instead of looking for an edge.
+pub type TamperAction = target_device::rtc::mode0::tampctrl::IN0ACT_A;
+
+/// The division of the RTC clock that samples debounced tamper inputs.
+pub type DebouncePrescaler = target_device::rtc::mode0::ctrlb::DEBF_A;
+
+/// The division of the RTC clock that the active layer output toggles at.
+pub type ActiveLayerPrescaler = target_device::rtc::mode0::ctrlb::ACTF_A;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TamperInput {
+	In0 = 0,
+	In1,
+	In2,
+	In3,
+	In4,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TamperEdge {
+	Falling,
+	Rising,
+}
+
+const INT_TAMPER: u16 = 1 << 14;
+
+// The tamper id register has one bit per input and the event input bit
+const TAMPID_INPUTS: u32 = 0x1f;
+const TAMPID_EVENT: u32 = 1 << 31;
+
+impl<Mode> RTC<Mode> {
+	/// Read one of the eight backup registers. Panics if `index` is out of
+	/// range.
+	pub fn backup(&self, index: usize) -> u32 {
+		self.rtc.mode0().bkup[index].read().bits()
+	}
+
+	/// Write one of the eight backup registers. Panics if `index` is out of
+	/// range.
+	pub fn set_backup(&mut self, index: usize, value: u32) {
+		self.rtc.mode0().bkup[index].write(|w| unsafe {
+			w.bits(value)
+		});
+	}
+
+	/// Make general purpose registers GP0 and GP1, for `index` 0 or 1, or
+	/// GP2 and GP3, for `index` 2 or 3, available. They take the place of
+	/// compare 0 or compare 2, which stop matching. Panics if `index` is out
+	/// of range.
+	pub fn enable_gp(&mut self, index: usize) {
+		assert!(index < 4, "there are four general purpose registers");
+		self.configure(|rtc| {
+			rtc.mode0().ctrlb.modify(|_, w| {
+				if index < 2 {
+					w.gp0en().set_bit()
+				} else {
+					w.gp2en().set_bit()
+				}
+			});
+		});
+	}
+
+	/// Read one of the four general purpose registers, it must have been
+	/// made available with `enable_gp`. Panics if `index` is out of range.
+	pub fn gp(&self, index: usize) -> u32 {
+		self.rtc.mode0().gp[index].read().bits()
+	}
+
+	/// Write one of the four general purpose registers, it must have been
+	/// made available with `enable_gp`. Panics if `index` is out of range.
+	pub fn set_gp(&mut self, index: usize, value: u32) {
+		let mode = self.rtc.mode0();
+		mode.gp[index].write(|w| unsafe {
+			w.bits(value)
+		});
+		// GPn sync busy is bit 16 + n
+		while mode.syncbusy.read().bits() & (1 << (16 + index)) != 0 {}
+	}
+
+	/// Set what a tamper input does and the edge it looks for. A debounced
+	/// input must hold its new level for the samples set by
+	/// `tamper_debounce` before it is detected.
+	pub fn tamper_input(&mut self, input: TamperInput, action: TamperAction, edge: TamperEdge, debounce: bool) {
+		let n = input as u32;
+		let action = u8::from(action) as u32;
+
+		self.configure(|rtc| {
+			rtc.mode0().tampctrl.modify(|r, w| unsafe {
+				// INnACT is two bits at 2n, TAMLVLn is at 16 + n and DEBNCn
+				// is at 24 + n
+				let mut bits = r.bits();
+				bits &= !((0b11 << (2 * n)) | (1 << (16 + n)) | (1 << (24 + n)));
+				bits |= action << (2 * n);
+				bits |= ((edge == TamperEdge::Rising) as u32) << (16 + n);
+				bits |= (debounce as u32) << (24 + n);
+				w.bits(bits)
+			});
+		});
+	}
+
+	/// Set how debounced tamper inputs are sampled, for all inputs. With
+	/// `majority` the input level is the majority of three samples, otherwise
+	/// it must be stable for three samples. `asynchronous` detects the first
+	/// edge without waiting for the debouncer.
+	pub fn tamper_debounce(&mut self, prescaler: DebouncePrescaler, majority: bool, asynchronous: bool) {
+		self.configure(|rtc| {
+			rtc.mode0().ctrlb.modify(|_, w| {
+				w.debf().variant(prescaler);
+				w.debmaj().bit(majority);
+				w.debasync().bit(asynchronous)
+			});
+		});
+	}
+
+	/// Drive the active layer on the OUT pin, to be compared with inputs
+	/// that use the `ACTL` action. Any break in the loop between OUT and the
+	/// input is detected as tamper.
+	pub fn active_layer(&mut self, prescaler: ActiveLayerPrescaler) {
+		self.configure(|rtc| {
+			rtc.mode0().ctrlb.modify(|_, w| {
+				w.actf().variant(prescaler);
+				w.rtcout().set_bit()
+			});
+		});
+	}
+
+	/// Erase the backup and general purpose registers when tamper is
+	/// detected. This clears BKUP0 to BKUP7 and GP0 to GP3, the count,
+	/// compare, timestamp and control registers are left as they are.
+	pub fn erase_on_tamper(&mut self, erase: bool) {
+		self.configure(|rtc| {
+			rtc.mode0().ctrla.modify(|_, w| {
+				w.bktrst().bit(erase);
+				w.gptrst().bit(erase)
+			});
+		});
+	}
+
+	/// Which tamper inputs have detected tamper, bit n is set for INn.
+	pub fn tamper_inputs(&self) -> u8 {
+		(self.rtc.mode0().tampid.read().bits() & TAMPID_INPUTS) as u8
+	}
+
+	/// Whether a tamper event was received from the event system.
+	pub fn is_tamper_event(&self) -> bool {
+		self.rtc.mode0().tampid.read().bits() & TAMPID_EVENT != 0
+	}
+
+	/// Clear the detected tamper inputs and event.
+	pub fn clear_tamper_inputs(&mut self) {
+		self.rtc.mode0().tampid.write(|w| unsafe {
+			w.bits(TAMPID_INPUTS | TAMPID_EVENT)
+		});
+	}
+
+	pub fn enable_tamper_interrupt(&mut self) {
+		self.enable_interrupts(INT_TAMPER);
+	}
+
+	pub fn disable_tamper_interrupt(&mut self) {
+		self.disable_interrupts(INT_TAMPER);
+	}
+
+	pub fn is_tamper(&self) -> bool {
+		self.is_interrupt(INT_TAMPER)
+	}
+
+	pub fn clear_tamper(&mut self) {
+		self.clear_interrupts(INT_TAMPER);
+	}
+
+	/// The tamper registers can only be written while the RTC is disabled.
+	/// It stops counting for the few cycles this takes.
+	fn configure<F: FnOnce(&target_device::RTC)>(&mut self, f: F) {
+		let mode = self.rtc.mode0();
+
+		mode.ctrla.modify(|_, w| {
+			w.enable().clear_bit()
+		});
+		while mode.syncbusy.read().enable().bit_is_set() {
+			cortex_m::asm::nop();
+		}
+
+		f(&self.rtc);
+
+		mode.ctrla.modify(|_, w| {
+			w.enable().set_bit()
+		});
+		while mode.syncbusy.read().enable().bit_is_set() {
+			cortex_m::asm::nop();
+		}
+	}
+}
+
+impl RTC<Count32Mode> {
+	/// The count captured by the last tamper input with the `CAPTURE` action.
+	pub fn timestamp(&self) -> u32 {
+		self.rtc.mode0().timestamp.read().bits()
+	}
+}
+
+impl RTC<Count16Mode> {
+	/// The count captured by the last tamper input with the `CAPTURE` action.
+	pub fn timestamp(&self) -> u16 {
+		self.rtc.mode1().timestamp.read().count().bits()
+	}
+}
+
+impl RTC<ClockMode> {
+	/// The date and time captured by the last tamper input with the
+	/// `CAPTURE` action.
+	pub fn timestamp(&self) -> DateTime {
+		let timestamp = self.rtc.mode2().timestamp.read();
+
+		DateTime {
+			year: BASE_YEAR + timestamp.year().bits() as u16,
+			month: timestamp.month().bits(),
+			day: timestamp.day().bits(),
+			hours: timestamp.hour().bits(),
+			minutes: timestamp.minute().bits(),
+			seconds: timestamp.second().bits(),
+		}
+	}
+}
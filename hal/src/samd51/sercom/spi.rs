@@ -1,4 +1,10 @@
 use crate::clock;
+use crate::dmac::{
+    self,
+    channel::{Interrupts, Settings, TriggerSource},
+    transfer::{ReadBuffer, WriteBuffer},
+    Transfer,
+};
 use crate::hal::spi::{FullDuplex, Mode, Phase, Polarity};
 use crate::sercom::pads::*;
 use crate::target_device::sercom0::SPI;
@@ -20,6 +26,145 @@ pub trait DipoDopo {
     fn dipo_dopo(&self) -> (u8, u8);
 }
 
+/// The DATA register of an SPI master, the peripheral end of the DMA
+/// transfers started by `write_dma` and `transfer_dma`.
+pub struct SpiData {
+    address: usize,
+}
+
+unsafe impl ReadBuffer for SpiData {
+    type Beat = u8;
+
+    fn dma_ptr(&mut self) -> *const u8 {
+        self.address as *const u8
+    }
+
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
+
+unsafe impl WriteBuffer for SpiData {
+    type Beat = u8;
+
+    fn dma_ptr(&mut self) -> *mut u8 {
+        self.address as *mut u8
+    }
+
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
+
+/// The SpiDma trait lets a DMA transfer hand an SPI master back in a usable
+/// state. You should not implement this trait for yourself; only the
+/// implementations in the sercom module make sense.
+pub trait SpiDma {
+    /// Wait for the last byte to be shifted out, then discard anything left in
+    /// the receive buffer and clear the overrun status.
+    fn finish_dma(&mut self);
+
+    /// Discard anything left in the receive buffer and clear the overrun
+    /// status without waiting for the transmitter, after a failed transfer.
+    fn abort_dma(&mut self);
+}
+
+/// A DMA write in progress on an SPI master, as returned by `write_dma`.
+pub struct SpiDmaWrite<SPI, Ch, B> {
+    spi: SPI,
+    transfer: Transfer<Ch, B, SpiData>,
+}
+
+impl<SPI, Ch, B> SpiDmaWrite<SPI, Ch, B>
+where
+    SPI: SpiDma,
+    Ch: dmac::Channel,
+{
+    /// Whether the DMAC has written the last byte to the SPI master, the
+    /// byte may still be shifting out.
+    pub fn is_complete(&self) -> bool {
+        self.transfer.is_complete()
+    }
+
+    /// Block until the last byte has been shifted out, then release the SPI
+    /// master, channel and buffer. If the DMAC stopped with an error some
+    /// bytes were not written.
+    pub fn wait(self) -> Result<(SPI, Ch, B), (dmac::transfer::Error, SPI, Ch, B)> {
+        let mut spi = self.spi;
+        match self.transfer.wait() {
+            Ok((channel, buffer, _)) => {
+                spi.finish_dma();
+                Ok((spi, channel, buffer))
+            }
+            Err((error, channel, buffer, _)) => {
+                spi.abort_dma();
+                Err((error, spi, channel, buffer))
+            }
+        }
+    }
+}
+
+/// A full duplex DMA transfer in progress on an SPI master, as returned by
+/// `transfer_dma`.
+pub struct SpiDmaTransfer<SPI, TxCh, RxCh, S, D> {
+    spi: SPI,
+    tx: Transfer<TxCh, S, SpiData>,
+    rx: Transfer<RxCh, SpiData, D>,
+}
+
+impl<SPI, TxCh, RxCh, S, D> SpiDmaTransfer<SPI, TxCh, RxCh, S, D>
+where
+    SPI: SpiDma,
+    TxCh: dmac::Channel,
+    RxCh: dmac::Channel,
+{
+    /// Whether the last byte has been received into the destination.
+    pub fn is_complete(&self) -> bool {
+        self.tx.is_complete() && self.rx.is_complete()
+    }
+
+    /// Block until the last byte has been received, then release the SPI
+    /// master, channels and buffers. If the DMAC stopped either side with an
+    /// error the destination may have been partially written.
+    pub fn wait(self) -> Result<(SPI, TxCh, RxCh, S, D), (dmac::transfer::Error, SPI, TxCh, RxCh, S, D)> {
+        let mut spi = self.spi;
+        let (error, tx_channel, source) = match self.tx.wait() {
+            Ok((channel, source, _)) => (None, channel, source),
+            Err((error, channel, source, _)) => (Some(error), channel, source),
+        };
+        let (error, rx_channel, destination) = match error {
+            // Nothing more arrives once the writes have failed
+            Some(error) => {
+                let (channel, _, destination) = self.rx.stop();
+                (Some(error), channel, destination)
+            }
+            None => match self.rx.wait() {
+                Ok((channel, _, destination)) => (None, channel, destination),
+                Err((error, channel, _, destination)) => (Some(error), channel, destination),
+            },
+        };
+
+        match error {
+            None => {
+                spi.finish_dma();
+                Ok((spi, tx_channel, rx_channel, source, destination))
+            }
+            Some(error) => {
+                spi.abort_dma();
+                Err((error, spi, tx_channel, rx_channel, source, destination))
+            }
+        }
+    }
+}
+
 /// Define an SPIMasterX type for the given Sercom number.
 ///
 /// Also defines the valid "pad to spi function" mappings for this instance so
@@ -173,6 +318,112 @@ macro_rules! spi_master {
                 fn spi(&mut self) -> &SPI {
                     &self.sercom.spi()
                 }
+
+                /// Write `buffer` out with DMA on `channel`, discarding the
+                /// received bytes. The SPI master, channel and buffer are
+                /// handed back by the returned transfer once it is complete.
+                pub fn write_dma<Ch, B>(mut self, channel: Ch, buffer: B) -> SpiDmaWrite<Self, Ch, B>
+                where
+                    Ch: dmac::Channel,
+                    B: ReadBuffer<Beat = u8>,
+                {
+                    self.flush_rx();
+
+                    let data = self.dma_data();
+                    let transfer = Transfer::start(
+                        channel,
+                        buffer,
+                        data,
+                        TriggerSource::[<$Sercom Tx>],
+                        Settings::default(),
+                        Interrupts::default(),
+                    );
+
+                    SpiDmaWrite {
+                        spi: self,
+                        transfer,
+                    }
+                }
+
+                /// Write `source` out with DMA on `tx_channel` while reading
+                /// the same number of bytes into `destination` on
+                /// `rx_channel`. The SPI master, channels and buffers are
+                /// handed back by the returned transfer once it is complete.
+                ///
+                /// Panics if `source` and `destination` differ in length.
+                pub fn transfer_dma<TxCh, RxCh, S, D>(
+                    mut self,
+                    tx_channel: TxCh,
+                    rx_channel: RxCh,
+                    source: S,
+                    destination: D,
+                ) -> SpiDmaTransfer<Self, TxCh, RxCh, S, D>
+                where
+                    TxCh: dmac::Channel,
+                    RxCh: dmac::Channel,
+                    S: ReadBuffer<Beat = u8>,
+                    D: WriteBuffer<Beat = u8>,
+                {
+                    assert!(source.buffer_len() == destination.buffer_len(), "source and destination lengths differ");
+
+                    self.flush_rx();
+
+                    // The receive channel must be running before the first
+                    // byte is sent
+                    let data = self.dma_data();
+                    let rx = Transfer::start(
+                        rx_channel,
+                        data,
+                        destination,
+                        TriggerSource::[<$Sercom Rx>],
+                        Settings::default(),
+                        Interrupts::default(),
+                    );
+
+                    let data = self.dma_data();
+                    let tx = Transfer::start(
+                        tx_channel,
+                        source,
+                        data,
+                        TriggerSource::[<$Sercom Tx>],
+                        Settings::default(),
+                        Interrupts::default(),
+                    );
+
+                    SpiDmaTransfer {
+                        spi: self,
+                        tx,
+                        rx,
+                    }
+                }
+
+                /// The DATA register as a DMA source or destination
+                fn dma_data(&mut self) -> SpiData {
+                    SpiData {
+                        address: &self.spi().data as *const _ as usize,
+                    }
+                }
+
+                /// Discard any received bytes and clear the overrun status
+                fn flush_rx(&mut self) {
+                    while self.spi().intflag.read().rxc().bit_is_set() {
+                        self.spi().data.read();
+                    }
+                    self.spi().status.write(|w| w.bufovf().set_bit());
+                    self.spi().intflag.write(|w| w.error().set_bit());
+                }
+            }
+
+            impl<MISO, MOSI, SCK> SpiDma for $Type<MISO, MOSI, SCK> {
+                fn finish_dma(&mut self) {
+                    // txc is transmit complete
+                    while self.spi().intflag.read().txc().bit_is_clear() {}
+                    self.flush_rx();
+                }
+
+                fn abort_dma(&mut self) {
+                    self.flush_rx();
+                }
             }
 
             impl<MISO, MOSI, SCK> FullDuplex<u8> for $Type<MISO, MOSI, SCK> {
@@ -0,0 +1,312 @@
+//! The two channel 12 bit digital to analog converter.
+//!
+//! DAC0 drives `VOUT0` on PA02 and DAC1 drives `VOUT1` on PA05, both can also
+//! feed the analog comparators and the ADCs internally. Values are written
+//! straight to an output with `write`, or through its data buffer, which is
+//! moved to the output on each start conversion event. Fed by DMA from the
+//! empty trigger, the buffer plays back a waveform at the rate of the events,
+//! for example a timer overflow.
+//!
+//! ```ignore
+//! let mut dac = Dac::init(&mut peripherals.MCLK, &dac_clock, peripherals.DAC, Reference::VDDANA);
+//! let mut a0 = pins.a0.into_function_b(&mut pins.port);
+//! dac.enable(&mut a0);
+//! dac.write(DacChannel::_0, 2048);
+//! ```
+use core::marker::PhantomData;
+
+use crate::{
+    target_device,
+    clock,
+    gpio,
+    events,
+    time::Hertz,
+    dmac::{
+        self,
+        channel::{Interrupts, Settings, TriggerSource},
+        transfer::WriteBuffer,
+        Transfer,
+    },
+};
+
+/// The voltage that the full scale output corresponds to, for both channels
+pub type Reference = target_device::dac::ctrlb::REFSEL_A;
+
+/// The largest value the DAC converts
+pub const MAX: u16 = (1 << 12) - 1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DacChannel {
+    _0,
+    _1,
+}
+
+/// A pin that a DAC channel can drive. You should not implement this trait
+/// yourself, only the implementations in this module make sense.
+pub trait DacPin {
+    const CHANNEL: DacChannel;
+}
+
+impl DacPin for gpio::Pa2<gpio::PfB> {
+    const CHANNEL: DacChannel = DacChannel::_0;
+}
+
+impl DacPin for gpio::Pa5<gpio::PfB> {
+    const CHANNEL: DacChannel = DacChannel::_1;
+}
+
+pub struct Dac {
+    dac: target_device::DAC,
+    freq: Hertz,
+}
+
+impl Dac {
+    /// Configure the DAC with both channels disabled. The clock must not be
+    /// faster than 12MHz.
+    pub fn init(mclk: &mut target_device::MCLK, clock: &clock::DacClock, dac: target_device::DAC, reference: Reference) -> Self {
+        mclk.apbdmask.modify(|_, w| w.dac_().set_bit());
+
+        dac.ctrla.write(|w| w.swrst().set_bit());
+        while dac.syncbusy.read().swrst().bit_is_set() {}
+
+        dac.ctrlb.write(|w| w.refsel().variant(reference));
+
+        dac.ctrla.write(|w| w.enable().set_bit());
+        while dac.syncbusy.read().enable().bit_is_set() {}
+
+        Dac {
+            dac,
+            freq: clock.freq(),
+        }
+    }
+
+    /// Select the voltage that the full scale output of both channels
+    /// corresponds to.
+    pub fn reference(&mut self, reference: Reference) {
+        self.configure(|dac| {
+            dac.ctrlb.modify(|_, w| w.refsel().variant(reference));
+        });
+    }
+
+    /// Enable the channel that drives `pin`, waiting for it to start up.
+    pub fn enable<PIN: DacPin>(&mut self, _pin: &mut PIN) {
+        self.enable_channel(PIN::CHANNEL);
+    }
+
+    /// Enable `channel` without a pin, for the analog comparators and the
+    /// ADCs, waiting for it to start up.
+    pub fn enable_channel(&mut self, channel: DacChannel) {
+        let freq = self.freq.0;
+        self.configure(|dac| {
+            dac.dacctrl[channel as usize].write(|w| {
+                // The output current has to suit the clock
+                if freq <= 1_200_000 {
+                    w.cctrl().cc100k();
+                } else if freq <= 6_000_000 {
+                    w.cctrl().cc1m();
+                } else {
+                    w.cctrl().cc12m();
+                }
+                // Refresh the held output every 60us
+                unsafe { w.refresh().bits(2) };
+                w.enable().set_bit()
+            });
+        });
+
+        let ready = 1 << channel as u8;
+        while self.dac.status.read().bits() & ready == 0 {}
+    }
+
+    /// Disable `channel`, its pin is left floating.
+    pub fn disable(&mut self, channel: DacChannel) {
+        self.configure(|dac| {
+            dac.dacctrl[channel as usize].modify(|_, w| w.enable().clear_bit());
+        });
+    }
+
+    /// Convert `value`, of up to 12 bits, on `channel`, blocking until the
+    /// output has been updated.
+    pub fn write(&mut self, channel: DacChannel, value: u16) {
+        let n = channel as usize;
+        self.dac.data[n].write(|w| unsafe { w.data().bits(value.min(MAX)) });
+        // DATAn sync busy is bit 2 + n, EOCn is bit 2 + n of STATUS
+        while self.dac.syncbusy.read().bits() & (1 << (2 + n)) != 0 {}
+        while self.dac.status.read().bits() & (1 << (2 + n)) == 0 {}
+    }
+
+    /// Load `value` into the data buffer of `channel`, it is converted on the
+    /// next start conversion event.
+    pub fn write_buffered(&mut self, channel: DacChannel, value: u16) {
+        let n = channel as usize;
+        self.dac.databuf[n].write(|w| unsafe { w.databuf().bits(value.min(MAX)) });
+        // DATABUFn sync busy is bit 4 + n
+        while self.dac.syncbusy.read().bits() & (1 << (4 + n)) != 0 {}
+    }
+
+    /// Convert the data buffer of `channel` on each event, returning the user
+    /// to subscribe to an event channel.
+    pub fn start_conversion_event(&mut self, channel: DacChannel) -> events::UserHandle<target_device::DAC> {
+        self.configure(|dac| {
+            dac.evctrl.modify(|r, w| unsafe {
+                // STARTEIn is bit n
+                w.bits(r.bits() | (1 << channel as u8))
+            });
+        });
+
+        events::UserHandle::new(match channel {
+            DacChannel::_0 => events::User::DAC_START0,
+            DacChannel::_1 => events::User::DAC_START1,
+        })
+    }
+
+    /// Enable the data buffer empty event output of `channel`, returning the
+    /// generator to publish on an event channel.
+    pub fn empty_event(&mut self, channel: DacChannel) -> events::GeneratorHandle<target_device::DAC> {
+        self.configure(|dac| {
+            dac.evctrl.modify(|r, w| unsafe {
+                // EMPTYEOn is bit 2 + n
+                w.bits(r.bits() | (1 << (2 + channel as u8)))
+            });
+        });
+
+        events::GeneratorHandle::new(match channel {
+            DacChannel::_0 => events::Generator::DAC_EMPTY0,
+            DacChannel::_1 => events::Generator::DAC_EMPTY1,
+        })
+    }
+
+    /// Whether the data buffer of `channel` has been converted and can take a
+    /// new value.
+    pub fn is_empty(&self, channel: DacChannel) -> bool {
+        self.dac.intflag.read().bits() & (1 << (2 + channel as u8)) != 0
+    }
+
+    /// Whether a start conversion event arrived on `channel` before its data
+    /// buffer was written.
+    pub fn is_underrun(&self, channel: DacChannel) -> bool {
+        self.dac.intflag.read().bits() & (1 << channel as u8) != 0
+    }
+
+    pub fn clear_underrun(&mut self, channel: DacChannel) {
+        self.dac.intflag.write(|w| unsafe { w.bits(1 << channel as u8) });
+    }
+
+    /// Play `samples` through the data buffer of `channel` with DMA
+    /// `dma_channel`, one per start conversion event. The events should be
+    /// connected first, see `start_conversion_event`.
+    ///
+    /// Panics if there are fewer than two samples.
+    pub fn play<Ch>(
+        mut self,
+        channel: DacChannel,
+        samples: &'static [u16],
+        dma_channel: Ch,
+        settings: Settings,
+        interrupts: Interrupts,
+    ) -> DacPlayback<Ch>
+    where
+        Ch: dmac::Channel,
+    {
+        // Convert the first sample on the first event, the rest are loaded
+        // as each is converted
+        assert!(samples.len() >= 2, "fewer than two samples to play");
+        let (first, rest) = samples.split_at(1);
+        self.write_buffered(channel, first[0]);
+
+        let trigger = match channel {
+            DacChannel::_0 => TriggerSource::DacEmpty0,
+            DacChannel::_1 => TriggerSource::DacEmpty1,
+        };
+        let transfer = Transfer::start(
+            dma_channel,
+            rest,
+            DataBuffer { channel, _dac: PhantomData },
+            trigger,
+            settings,
+            interrupts,
+        );
+
+        DacPlayback {
+            dac: self,
+            samples,
+            transfer,
+        }
+    }
+
+    /// Disable the DAC and release it.
+    pub fn free(self) -> target_device::DAC {
+        self.dac.ctrla.modify(|_, w| w.enable().clear_bit());
+        while self.dac.syncbusy.read().enable().bit_is_set() {}
+
+        self.dac
+    }
+
+    /// CTRLB, EVCTRL and DACCTRL can only be written while the DAC is
+    /// disabled, it is enabled again afterwards.
+    fn configure<F: FnOnce(&target_device::DAC)>(&mut self, f: F) {
+        self.dac.ctrla.modify(|_, w| w.enable().clear_bit());
+        while self.dac.syncbusy.read().enable().bit_is_set() {}
+
+        f(&self.dac);
+
+        self.dac.ctrla.modify(|_, w| w.enable().set_bit());
+        while self.dac.syncbusy.read().enable().bit_is_set() {}
+    }
+}
+
+/// The data buffer register of a DAC channel as a DMA destination
+pub struct DataBuffer {
+    channel: DacChannel,
+    _dac: PhantomData<target_device::DAC>,
+}
+
+unsafe impl WriteBuffer for DataBuffer {
+    type Beat = u16;
+
+    fn dma_ptr(&mut self) -> *mut u16 {
+        let dac = unsafe { &*target_device::DAC::ptr() };
+        &dac.databuf[self.channel as usize] as *const _ as *mut u16
+    }
+
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
+
+/// A DAC channel playing samples from memory, as returned by `Dac::play`.
+pub struct DacPlayback<Ch> {
+    dac: Dac,
+    samples: &'static [u16],
+    transfer: Transfer<Ch, &'static [u16], DataBuffer>,
+}
+
+impl<Ch> DacPlayback<Ch>
+where
+    Ch: dmac::Channel,
+{
+    /// Whether every sample has been loaded into the data buffer, the last
+    /// is converted on the next event.
+    pub fn is_complete(&self) -> bool {
+        self.transfer.is_complete()
+    }
+
+    /// Block until every sample has been loaded, then release the DAC,
+    /// channel and samples. If the DMAC stopped with an error some samples
+    /// were not played.
+    pub fn wait(self) -> Result<(Dac, Ch, &'static [u16]), (dmac::transfer::Error, Dac, Ch, &'static [u16])> {
+        match self.transfer.wait() {
+            Ok((channel, _, _)) => Ok((self.dac, channel, self.samples)),
+            Err((error, channel, _, _)) => Err((error, self.dac, channel, self.samples)),
+        }
+    }
+
+    /// Stop playing, then release the DAC, channel and samples.
+    pub fn stop(self) -> (Dac, Ch, &'static [u16]) {
+        let (channel, _, _) = self.transfer.stop();
+        (self.dac, channel, self.samples)
+    }
+}
@@ -0,0 +1,489 @@
+use core::ptr;
+
+use crate::events;
+
+use crate::target_device::{
+	self,
+	dmac::{
+		CHCTRLA,
+		CHCTRLB,
+		CHPRILVL,
+		CHEVCTRL,
+		CHINTENCLR,
+		CHINTENSET,
+		CHINTFLAG,
+		CHSTATUS,
+	},
+};
+
+use super::{
+	Descriptor,
+	BASE_DESCRIPTORS,
+	WRITEBACK_DESCRIPTORS,
+};
+
+/// The registers for a single DMAC channel.
+///
+/// Unlike the SAMD21 there is no CHID indirection, each of the 32 channels
+/// has its own 16 byte block of registers starting at CHCTRLA0. The PAC
+/// names every register individually, this overlays one of those blocks.
+#[repr(C)]
+struct ChannelRegisters {
+	chctrla: CHCTRLA,
+	chctrlb: CHCTRLB,
+	chprilvl: CHPRILVL,
+	chevctrl: CHEVCTRL,
+	_reserved: [u8; 5],
+	chintenclr: CHINTENCLR,
+	chintenset: CHINTENSET,
+	chintflag: CHINTFLAG,
+	chstatus: CHSTATUS,
+}
+
+fn registers(id: u8) -> &'static ChannelRegisters {
+	let dmac = unsafe { &*target_device::DMAC::ptr() };
+	let base = &dmac.chctrla0 as *const CHCTRLA as *const ChannelRegisters;
+	unsafe { &*base.offset(id as isize) }
+}
+
+mod sealed {
+	/// Keeps `Channel`, `EventInput` and `EventOutput` to the channels
+	/// defined in this module.
+	pub trait Sealed {}
+}
+
+/// A DMAC channel obtained from `DMAC::split`. This trait is sealed, only
+/// the channels in this module implement it.
+pub trait Channel: sealed::Sealed {
+	const ID: u8;
+
+	/// Configure the trigger, priority, burst and interrupts for this
+	/// channel, the channel is left disabled.
+	fn configure(&mut self, source: TriggerSource, action: Action, settings: Settings, interrupts: Interrupts) {
+		let registers = registers(Self::ID);
+
+		registers.chctrla.modify(|_, w| {
+			w.enable().clear_bit()
+		});
+		while registers.chctrla.read().enable().bit_is_set() {
+			cortex_m::asm::nop();
+		}
+
+		registers.chprilvl.write(|w| {
+			w.prilvl().variant(settings.priority)
+		});
+
+		registers.chctrla.write(|w| {
+			unsafe { w.trigsrc().bits(source as u8); }
+			w.trigact().variant(action);
+			w.burstlen().variant(settings.burst_length);
+			w.threshold().variant(settings.threshold);
+			w.runstdby().bit(settings.run_standby)
+		});
+
+		registers.chintenclr.write(|w| {
+			w.susp().set_bit();
+			w.tcmpl().set_bit();
+			w.terr().set_bit()
+		});
+		registers.chintenset.write(|w| {
+			w.susp().bit(interrupts.suspend);
+			w.tcmpl().bit(interrupts.transfer_complete);
+			w.terr().bit(interrupts.transfer_error)
+		});
+	}
+
+	fn enable(&mut self) {
+		registers(Self::ID).chctrla.modify(|_, w| {
+			w.enable().set_bit()
+		});
+	}
+
+	fn disable(&mut self) {
+		let registers = registers(Self::ID);
+
+		registers.chctrla.modify(|_, w| {
+			w.enable().clear_bit()
+		});
+		while registers.chctrla.read().enable().bit_is_set() {
+			cortex_m::asm::nop();
+		}
+	}
+
+	fn is_enabled(&self) -> bool {
+		registers(Self::ID).chctrla.read().enable().bit_is_set()
+	}
+
+	fn trigger(&mut self) {
+		let dmac = unsafe { &*target_device::DMAC::ptr() };
+		dmac.swtrigctrl.write(|w| unsafe {
+			w.bits(1 << Self::ID)
+		});
+	}
+
+	/// Whether the DMAC disabled this channel because of a transfer error.
+	fn is_failed(&self) -> bool {
+		registers(Self::ID).chintflag.read().terr().bit_is_set()
+	}
+
+	/// Clear this channel's interrupt flags, returning the flags that were
+	/// set.
+	fn clear_interrupts(&mut self) -> Interrupts {
+		let registers = registers(Self::ID);
+		let flags = registers.chintflag.read();
+
+		let interrupts = Interrupts {
+			suspend: flags.susp().bit_is_set(),
+			transfer_complete: flags.tcmpl().bit_is_set(),
+			transfer_error: flags.terr().bit_is_set(),
+		};
+
+		registers.chintflag.write(|w| {
+			w.susp().bit(interrupts.suspend);
+			w.tcmpl().bit(interrupts.transfer_complete);
+			w.terr().bit(interrupts.transfer_error)
+		});
+
+		interrupts
+	}
+
+	/// The first descriptor of this channel, the DMAC fetches it from here
+	/// when the channel is triggered.
+	fn descriptor_ptr(&self) -> *const Descriptor {
+		unsafe { (ptr::addr_of!(BASE_DESCRIPTORS) as *const Descriptor).add(Self::ID as usize) }
+	}
+
+	/// Replace the first descriptor of this channel.
+	///
+	/// This is unsafe because the DMAC will read from and write to whatever
+	/// addresses the descriptor (and any descriptors it links to) point at.
+	/// Those addresses must remain valid until the transfer has completed.
+	unsafe fn set_descriptor(&mut self, descriptor: Descriptor) {
+		ptr::write_volatile((ptr::addr_of_mut!(BASE_DESCRIPTORS) as *mut Descriptor).add(Self::ID as usize), descriptor);
+	}
+
+	/// The state of the in progress descriptor, `btcnt` is the number of beats
+	/// remaining.
+	fn writeback(&self) -> Descriptor {
+		unsafe {
+			ptr::read_volatile((ptr::addr_of!(WRITEBACK_DESCRIPTORS) as *const Descriptor).add(Self::ID as usize))
+		}
+	}
+}
+
+/// A DMAC channel that can be triggered or controlled from the event system.
+/// Only channels 0-7 have an event input.
+pub trait EventInput: Channel + events::EventUser + sealed::Sealed {
+	/// Must be called while the channel is disabled.
+	fn enable_event_input(&mut self, action: EventInputAction) {
+		registers(Self::ID).chevctrl.modify(|_, w| {
+			w.evie().set_bit();
+			w.evact().variant(action)
+		});
+	}
+
+	fn disable_event_input(&mut self) {
+		registers(Self::ID).chevctrl.modify(|_, w| {
+			w.evie().clear_bit()
+		});
+	}
+}
+
+/// A DMAC channel that can generate events, either as selected by
+/// `Btctrl::evosel` or for the ongoing trigger action. Only channels 0-3 have
+/// an event output.
+pub trait EventOutput: Channel + events::EventGenerator + sealed::Sealed {
+	/// Must be called while the channel is disabled.
+	fn enable_event_output(&mut self, mode: EventOutputMode) {
+		registers(Self::ID).chevctrl.modify(|_, w| {
+			w.evoe().set_bit();
+			w.evomode().variant(mode)
+		});
+	}
+
+	fn disable_event_output(&mut self) {
+		registers(Self::ID).chevctrl.modify(|_, w| {
+			w.evoe().clear_bit()
+		});
+	}
+}
+
+macro_rules! channels {
+	($($Ch:ident: ($field:ident, $num:expr),)+) => {
+		/// The DMAC channels, as returned by `DMAC::split`.
+		pub struct Channels {
+			$(
+				pub $field: $Ch,
+			)+
+		}
+
+		impl Channels {
+			pub(super) fn new() -> Self {
+				Channels {
+					$(
+						$field: $Ch { _private: () },
+					)+
+				}
+			}
+		}
+
+		$(
+			pub struct $Ch {
+				_private: (),
+			}
+
+			impl sealed::Sealed for $Ch {}
+
+			impl Channel for $Ch {
+				const ID: u8 = $num;
+			}
+		)+
+	}
+}
+
+channels! {
+	Ch0: (ch0, 0),
+	Ch1: (ch1, 1),
+	Ch2: (ch2, 2),
+	Ch3: (ch3, 3),
+	Ch4: (ch4, 4),
+	Ch5: (ch5, 5),
+	Ch6: (ch6, 6),
+	Ch7: (ch7, 7),
+	Ch8: (ch8, 8),
+	Ch9: (ch9, 9),
+	Ch10: (ch10, 10),
+	Ch11: (ch11, 11),
+	Ch12: (ch12, 12),
+	Ch13: (ch13, 13),
+	Ch14: (ch14, 14),
+	Ch15: (ch15, 15),
+	Ch16: (ch16, 16),
+	Ch17: (ch17, 17),
+	Ch18: (ch18, 18),
+	Ch19: (ch19, 19),
+	Ch20: (ch20, 20),
+	Ch21: (ch21, 21),
+	Ch22: (ch22, 22),
+	Ch23: (ch23, 23),
+	Ch24: (ch24, 24),
+	Ch25: (ch25, 25),
+	Ch26: (ch26, 26),
+	Ch27: (ch27, 27),
+	Ch28: (ch28, 28),
+	Ch29: (ch29, 29),
+	Ch30: (ch30, 30),
+	Ch31: (ch31, 31),
+}
+
+macro_rules! event_input_channels {
+	($($Ch:ident: $user:ident,)+) => {
+		$(
+			impl events::EventUser for $Ch {
+				fn event_user(&self) -> events::User {
+					events::User::$user
+				}
+			}
+
+			impl EventInput for $Ch {}
+		)+
+	}
+}
+
+macro_rules! event_output_channels {
+	($($Ch:ident: $generator:ident,)+) => {
+		$(
+			impl events::EventGenerator for $Ch {
+				fn event_generator(&self) -> events::Generator {
+					events::Generator::$generator
+				}
+			}
+
+			impl EventOutput for $Ch {}
+		)+
+	}
+}
+
+event_input_channels! {
+	Ch0: DMAC_CH0,
+	Ch1: DMAC_CH1,
+	Ch2: DMAC_CH2,
+	Ch3: DMAC_CH3,
+	Ch4: DMAC_CH4,
+	Ch5: DMAC_CH5,
+	Ch6: DMAC_CH6,
+	Ch7: DMAC_CH7,
+}
+
+event_output_channels! {
+	Ch0: DMAC_CH0,
+	Ch1: DMAC_CH1,
+	Ch2: DMAC_CH2,
+	Ch3: DMAC_CH3,
+}
+
+#[allow(unused)]
+#[derive(Clone, Copy)]
+pub enum TriggerSource {
+	/// Software or event triggered only
+	Software = 0x00,
+
+	RtcTimestamp = 0x01,
+
+	DsuDcc0 = 0x02,
+	DsuDcc1 = 0x03,
+
+	Sercom0Rx = 0x04,
+	Sercom0Tx = 0x05,
+
+	Sercom1Rx = 0x06,
+	Sercom1Tx = 0x07,
+
+	Sercom2Rx = 0x08,
+	Sercom2Tx = 0x09,
+
+	Sercom3Rx = 0x0A,
+	Sercom3Tx = 0x0B,
+
+	Sercom4Rx = 0x0C,
+	Sercom4Tx = 0x0D,
+
+	Sercom5Rx = 0x0E,
+	Sercom5Tx = 0x0F,
+
+	Sercom6Rx = 0x10,
+	Sercom6Tx = 0x11,
+
+	Sercom7Rx = 0x12,
+	Sercom7Tx = 0x13,
+
+	Can0Debug = 0x14,
+	Can1Debug = 0x15,
+
+	Tcc0Ovf = 0x16,
+	Tcc0Mc0 = 0x17,
+	Tcc0Mc1 = 0x18,
+	Tcc0Mc2 = 0x19,
+	Tcc0Mc3 = 0x1A,
+	Tcc0Mc4 = 0x1B,
+	Tcc0Mc5 = 0x1C,
+
+	Tcc1Ovf = 0x1D,
+	Tcc1Mc0 = 0x1E,
+	Tcc1Mc1 = 0x1F,
+	Tcc1Mc2 = 0x20,
+	Tcc1Mc3 = 0x21,
+
+	Tcc2Ovf = 0x22,
+	Tcc2Mc0 = 0x23,
+	Tcc2Mc1 = 0x24,
+	Tcc2Mc2 = 0x25,
+
+	Tcc3Ovf = 0x26,
+	Tcc3Mc0 = 0x27,
+	Tcc3Mc1 = 0x28,
+
+	Tcc4Ovf = 0x29,
+	Tcc4Mc0 = 0x2A,
+	Tcc4Mc1 = 0x2B,
+
+	Tc0Ovf = 0x2C,
+	Tc0Mc0 = 0x2D,
+	Tc0Mc1 = 0x2E,
+
+	Tc1Ovf = 0x2F,
+	Tc1Mc0 = 0x30,
+	Tc1Mc1 = 0x31,
+
+	Tc2Ovf = 0x32,
+	Tc2Mc0 = 0x33,
+	Tc2Mc1 = 0x34,
+
+	Tc3Ovf = 0x35,
+	Tc3Mc0 = 0x36,
+	Tc3Mc1 = 0x37,
+
+	Tc4Ovf = 0x38,
+	Tc4Mc0 = 0x39,
+	Tc4Mc1 = 0x3A,
+
+	Tc5Ovf = 0x3B,
+	Tc5Mc0 = 0x3C,
+	Tc5Mc1 = 0x3D,
+
+	Tc6Ovf = 0x3E,
+	Tc6Mc0 = 0x3F,
+	Tc6Mc1 = 0x40,
+
+	Tc7Ovf = 0x41,
+	Tc7Mc0 = 0x42,
+	Tc7Mc1 = 0x43,
+
+	/// ADC0 Result Ready
+	Adc0ResultReady = 0x44,
+	/// ADC0 Sequencing
+	Adc0Sequence = 0x45,
+
+	/// ADC1 Result Ready
+	Adc1ResultReady = 0x46,
+	/// ADC1 Sequencing
+	Adc1Sequence = 0x47,
+
+	DacEmpty0 = 0x48,
+	DacEmpty1 = 0x49,
+	DacResultReady0 = 0x4A,
+	DacResultReady1 = 0x4B,
+
+	I2sRx0 = 0x4C,
+	I2sRx1 = 0x4D,
+	I2sTx0 = 0x4E,
+	I2sTx1 = 0x4F,
+
+	PccRx = 0x50,
+
+	AesWrite = 0x51,
+	AesRead = 0x52,
+
+	QspiRx = 0x53,
+	QspiTx = 0x54,
+}
+
+pub type Action = target_device::dmac::chctrla::TRIGACT_A;
+
+pub type Priority = target_device::dmac::chprilvl::PRILVL_A;
+
+/// Number of beats transferred per burst
+pub type BurstLength = target_device::dmac::chctrla::BURSTLEN_A;
+
+/// Number of beats read from the source before the destination write starts
+pub type Threshold = target_device::dmac::chctrla::THRESHOLD_A;
+
+#[derive(Clone, Copy)]
+pub struct Settings {
+	pub priority: Priority,
+	pub burst_length: BurstLength,
+	pub threshold: Threshold,
+	pub run_standby: bool,
+}
+
+impl Default for Settings {
+	fn default() -> Self {
+		Settings {
+			priority: Priority::LVL0,
+			burst_length: BurstLength::SINGLE,
+			threshold: Threshold::_1BEAT,
+			run_standby: false,
+		}
+	}
+}
+
+pub type EventInputAction = target_device::dmac::chevctrl::EVACT_A;
+
+pub type EventOutputMode = target_device::dmac::chevctrl::EVOMODE_A;
+
+#[derive(Clone, Copy, Default)]
+pub struct Interrupts {
+	pub suspend: bool,
+	pub transfer_complete: bool,
+	pub transfer_error: bool,
+}
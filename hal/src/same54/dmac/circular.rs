@@ -0,0 +1,243 @@
+use core::{
+	ptr,
+	sync::atomic::{
+		compiler_fence,
+		Ordering,
+	},
+};
+
+use super::{
+	channel::{
+		Action,
+		Channel,
+		Interrupts,
+		Settings,
+		TriggerSource,
+	},
+	descriptor::{
+		self,
+		BlockAction,
+		Btctrl,
+		Descriptor,
+		EventOutputAction,
+		Stepsel,
+		Stepsize,
+	},
+	transfer::{
+		descriptor_address,
+		Beat,
+		ReadBuffer,
+	},
+};
+
+/// Storage for a `CircularTransfer`: the samples, split into two halves, and
+/// the descriptor for the second half. It must be declared `static` so the
+/// DMAC can keep writing to it.
+#[repr(C)]
+pub struct DoubleBuffer<S> {
+	second: Descriptor,
+	samples: S,
+}
+
+impl<S> DoubleBuffer<S> {
+	pub const fn new(samples: S) -> Self {
+		Self {
+			second: descriptor::default(),
+			samples,
+		}
+	}
+
+	/// The samples, once the transfer has been stopped.
+	pub fn samples(&self) -> &S {
+		&self.samples
+	}
+}
+
+/// One half of a `DoubleBuffer`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Half {
+	First,
+	Second,
+}
+
+impl Half {
+	fn other(self) -> Half {
+		match self {
+			Half::First => Half::Second,
+			Half::Second => Half::First,
+		}
+	}
+}
+
+/// The DMAC overwrote samples before they were read
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Overrun;
+
+/// A transfer that fills the two halves of a `DoubleBuffer` in turn until it
+/// is stopped, one burst per trigger.
+///
+/// The channel's transfer complete flag is set each time a half fills, enable
+/// the `transfer_complete` interrupt to be told about it or call `poll`.
+pub struct CircularTransfer<Ch, Src, S: 'static> {
+	channel: Ch,
+	source: Src,
+	buffer: &'static mut DoubleBuffer<S>,
+	// The first sample, the samples are only reached through this pointer
+	// while the DMAC is writing them, never through a reference
+	samples: *const u8,
+	half_len: usize,
+	// The half that should fill next if every half has been polled
+	next: Half,
+}
+
+impl<Ch, Src, S, B> CircularTransfer<Ch, Src, S>
+where
+	Ch: Channel,
+	Src: ReadBuffer<Beat = B>,
+	S: AsRef<[B]> + AsMut<[B]>,
+	B: Beat,
+{
+	/// Configure the channel and start filling the first half.
+	///
+	/// Panics if the buffer is empty, has an odd length, or if each half is
+	/// longer than 65535 beats.
+	pub fn start(mut channel: Ch, mut source: Src, buffer: &'static mut DoubleBuffer<S>, trigger: TriggerSource, settings: Settings, interrupts: Interrupts) -> Self {
+		let len = buffer.samples.as_ref().len();
+		assert!(len > 0 && len % 2 == 0, "buffer length must be even");
+		let half = len / 2;
+		assert!(half <= u16::max_value() as usize, "each half is longer than 65535 beats");
+
+		let src_inc = source.incrementing();
+		let srcaddr = descriptor_address(source.dma_ptr(), src_inc, half);
+		let samples = buffer.samples.as_mut().as_mut_ptr();
+
+		let btctrl = {
+			let mut btctrl = Btctrl(0);
+			btctrl.set_stepsize(Stepsize::X1);
+			btctrl.set_stepsel(Stepsel::StepDst);
+			btctrl.set_dstinc(true);
+			btctrl.set_srcinc(src_inc);
+			btctrl.set_beatsize(B::BEATSIZE);
+			btctrl.set_blockact(BlockAction::Interrupt);
+			btctrl.set_evosel(EventOutputAction::None);
+			btctrl.set_valid(true);
+			btctrl
+		};
+
+		let second_ptr = &buffer.second as *const Descriptor;
+
+		unsafe {
+			ptr::write_volatile(&mut buffer.second as *mut Descriptor, Descriptor {
+				btctrl,
+				btcnt: half as u16,
+				srcaddr,
+				dstaddr: descriptor_address(samples.add(half), true, half) as *mut u8,
+				descaddr: channel.descriptor_ptr(),
+			});
+		}
+
+		channel.configure(trigger, Action::BURST, settings, interrupts);
+		unsafe {
+			channel.set_descriptor(Descriptor {
+				btctrl,
+				btcnt: half as u16,
+				srcaddr,
+				dstaddr: descriptor_address(samples, true, half) as *mut u8,
+				descaddr: second_ptr,
+			});
+		}
+
+		compiler_fence(Ordering::SeqCst);
+
+		channel.enable();
+
+		CircularTransfer {
+			channel,
+			source,
+			buffer,
+			samples: samples as *const u8,
+			half_len: half,
+			next: Half::First,
+		}
+	}
+
+	/// The half that was most recently filled, or `WouldBlock` if none has
+	/// filled since the last call. The half is only stable until the DMAC
+	/// finishes the other one, so it should be read straight away.
+	///
+	/// Returns `Overrun` if the DMAC filled both halves since the last call,
+	/// and so overwrote a half that was never reported. Only the transfer
+	/// complete flag and the position of the DMAC are known, so three halves
+	/// look the same as one; polling from the transfer complete interrupt
+	/// keeps up with every half.
+	pub fn poll(&mut self) -> nb::Result<Half, Overrun> {
+		if !self.channel.clear_interrupts().transfer_complete {
+			return Err(nb::Error::WouldBlock);
+		}
+
+		compiler_fence(Ordering::SeqCst);
+
+		// The half that isn't being written is the one that just filled
+		let filled = self.in_progress().other();
+		let expected = self.next;
+		self.next = filled.other();
+
+		if filled == expected {
+			Ok(filled)
+		} else {
+			Err(nb::Error::Other(Overrun))
+		}
+	}
+
+	/// Copy the samples in `half` into `dest`, which must be as long as a
+	/// half.
+	///
+	/// Returns `Overrun` if the DMAC was writing to `half` before the copy
+	/// finished, some of the samples copied are then newer than the rest.
+	pub fn read_half(&self, half: Half, dest: &mut [B]) -> Result<(), Overrun> {
+		assert!(dest.len() == self.half_len, "destination is not as long as a half");
+
+		if self.in_progress() == half {
+			return Err(Overrun);
+		}
+
+		let samples = self.samples as *const B;
+		let start = match half {
+			Half::First => samples,
+			Half::Second => samples.wrapping_add(self.half_len),
+		};
+		for (i, sample) in dest.iter_mut().enumerate() {
+			*sample = unsafe { ptr::read_volatile(start.add(i)) };
+		}
+
+		compiler_fence(Ordering::SeqCst);
+
+		if self.in_progress() == half {
+			Err(Overrun)
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Stop the transfer, then release the channel, source and buffer.
+	pub fn stop(mut self) -> (Ch, Src, &'static mut DoubleBuffer<S>) {
+		self.channel.disable();
+		self.channel.clear_interrupts();
+
+		compiler_fence(Ordering::SeqCst);
+
+		(self.channel, self.source, self.buffer)
+	}
+
+	/// The half the DMAC is writing, the write back descriptor is a copy of
+	/// the one in progress.
+	fn in_progress(&self) -> Half {
+		let end = self.channel.writeback().dstaddr as *const B;
+
+		if end == (self.samples as *const B).wrapping_add(self.half_len) {
+			Half::First
+		} else {
+			Half::Second
+		}
+	}
+}
@@ -0,0 +1,75 @@
+use crate::target_device;
+
+pub mod descriptor;
+pub use descriptor::Descriptor;
+
+pub mod channel;
+pub use channel::{
+	Channel,
+	Channels,
+};
+
+pub mod buffer;
+
+pub mod transfer;
+pub use transfer::Transfer;
+
+pub mod circular;
+pub use circular::CircularTransfer;
+
+// Only accessed through the channel singletons handed out by `DMAC::split`,
+// each channel owns the descriptors at its own index.
+static mut BASE_DESCRIPTORS: [Descriptor; 32] = [descriptor::default(); 32];
+static mut WRITEBACK_DESCRIPTORS: [Descriptor; 32] = [descriptor::default(); 32];
+
+pub struct DMAC {
+	#[allow(dead_code)]
+	dmac: target_device::DMAC,
+}
+
+impl DMAC {
+	pub fn init(mclk: &mut target_device::MCLK, dmac: target_device::DMAC) -> Self {
+		mclk.ahbmask.modify(|_, w| w.dmac_().set_bit());
+
+		dmac.ctrl.modify(|_, w| {
+			w.dmaenable().clear_bit()
+		});
+		dmac.ctrl.write(|w| {
+			w.swrst().set_bit()
+		});
+		while dmac.ctrl.read().swrst().bit_is_set() {
+			cortex_m::asm::nop();
+		}
+
+		dmac.baseaddr.write(|w| {
+			unsafe { w.bits(core::ptr::addr_of!(BASE_DESCRIPTORS) as u32) }
+		});
+		dmac.wrbaddr.write(|w| {
+			unsafe { w.bits(core::ptr::addr_of_mut!(WRITEBACK_DESCRIPTORS) as u32) }
+		});
+
+		dmac.ctrl.modify(|_, w| {
+			w.dmaenable().set_bit();
+
+			w.lvlen3().set_bit();
+			w.lvlen2().set_bit();
+			w.lvlen1().set_bit();
+			w.lvlen0().set_bit()
+		});
+
+		dmac.dbgctrl.modify(|_, w| {
+			w.dbgrun().set_bit()
+		});
+
+		Self {
+			dmac,
+		}
+	}
+
+	/// Split the DMAC into its channels. Each channel can only be owned by a
+	/// single driver, event capable channels implement `channel::EventInput`
+	/// and `channel::EventOutput`.
+	pub fn split(self) -> Channels {
+		Channels::new()
+	}
+}
@@ -0,0 +1,108 @@
+use crate::{
+	target_device,
+	clock,
+};
+
+pub mod pin;
+
+/// The number of debounce clock ticks between samples, the tick is divided
+/// from the debounce clock by this factor.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebouncePrescaler {
+	Div2 = 0,
+	Div4,
+	Div8,
+	Div16,
+	Div32,
+	Div64,
+	Div128,
+	Div256,
+}
+
+/// The number of consecutive equal samples needed for a debounced pin to
+/// change state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebounceStates {
+	Three,
+	Seven,
+}
+
+/// The clock that ticks the debouncer sampling.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebounceClock {
+	/// The clock selected for the EIC at init.
+	Eic,
+	/// The low power 32kHz clock, which keeps running in standby.
+	Ulp32k,
+}
+
+pub struct EIC {
+	eic: target_device::EIC,
+}
+
+impl EIC {
+	/// Enable the EIC clocked by the given generic clock.
+	pub fn init(mclk: &mut target_device::MCLK, _clock: clock::EicClock, eic: target_device::EIC) -> Self {
+		Self::enable(mclk, eic, false)
+	}
+
+	/// Enable the EIC clocked by the low power 32kHz clock, so that filtered
+	/// and debounced external interrupts keep working in standby without a
+	/// generic clock.
+	pub fn init_with_ulp32k(mclk: &mut target_device::MCLK, eic: target_device::EIC) -> Self {
+		Self::enable(mclk, eic, true)
+	}
+
+	fn enable(mclk: &mut target_device::MCLK, eic: target_device::EIC, ulp32k: bool) -> Self {
+		mclk.apbamask.modify(|_, w| w.eic_().set_bit());
+
+		eic.ctrla.modify(|_, w| w.swrst().set_bit());
+		while eic.syncbusy.read().swrst().bit_is_set() {
+			cortex_m::asm::nop();
+		}
+
+		eic.ctrla.modify(|_, w| w.cksel().bit(ulp32k));
+
+		let mut eic = EIC {
+			eic,
+		};
+		eic.set_enabled(true);
+		eic
+	}
+
+	/// Set how the debouncer samples the pins that have debouncing enabled,
+	/// for all sixteen external interrupts.
+	pub fn debounce_prescaler(&mut self, prescaler: DebouncePrescaler, states: DebounceStates, clock: DebounceClock) {
+		let seven = states == DebounceStates::Seven;
+		self.configure(|eic| {
+			eic.dprescaler.write(|w| unsafe {
+				w.prescaler0().bits(prescaler as u8);
+				w.prescaler1().bits(prescaler as u8);
+				w.states0().bit(seven);
+				w.states1().bit(seven);
+				w.tickon().bit(clock == DebounceClock::Ulp32k)
+			});
+		});
+	}
+
+	/// The debounced state of every external interrupt pin, bit n is set if
+	/// EXTINTn is high.
+	pub fn pin_state(&self) -> u16 {
+		self.eic.pinstate.read().pinstate().bits()
+	}
+
+	/// Most of the EIC registers can only be written while it is disabled.
+	/// Edges on other pins are not seen in the short time it takes.
+	fn configure<F: FnOnce(&target_device::EIC)>(&mut self, f: F) {
+		self.set_enabled(false);
+		f(&self.eic);
+		self.set_enabled(true);
+	}
+
+	fn set_enabled(&mut self, enabled: bool) {
+		self.eic.ctrla.modify(|_, w| w.enable().bit(enabled));
+		while self.eic.syncbusy.read().enable().bit_is_set() {
+			cortex_m::asm::nop();
+		}
+	}
+}
@@ -0,0 +1,479 @@
+#![allow(non_camel_case_types)]
+
+use core::marker::PhantomData;
+
+use crate::{
+	target_device::{
+		self,
+		evsys::{
+			self,
+			CHANNEL,
+			CHINTENCLR,
+			CHINTENSET,
+			CHINTFLAG,
+			CHSTATUS,
+		},
+	},
+};
+
+/// The registers for a single EVSYS channel.
+///
+/// Unlike the SAMD21 there is no channel indirection, each of the 32
+/// channels has its own 8 byte block of registers starting at CHANNEL0.
+#[repr(C)]
+struct ChannelRegisters {
+	channel: CHANNEL,
+	chintenclr: CHINTENCLR,
+	chintenset: CHINTENSET,
+	chintflag: CHINTFLAG,
+	chstatus: CHSTATUS,
+}
+
+pub struct EventSys {
+	evsys: target_device::EVSYS,
+}
+
+#[derive(Clone, Copy)]
+pub enum Channel {
+	Channel0 = 0,
+	Channel1 = 1,
+	Channel2 = 2,
+	Channel3 = 3,
+	Channel4 = 4,
+	Channel5 = 5,
+	Channel6 = 6,
+	Channel7 = 7,
+	Channel8 = 8,
+	Channel9 = 9,
+	Channel10 = 10,
+	Channel11 = 11,
+	Channel12 = 12,
+	Channel13 = 13,
+	Channel14 = 14,
+	Channel15 = 15,
+	Channel16 = 16,
+	Channel17 = 17,
+	Channel18 = 18,
+	Channel19 = 19,
+	Channel20 = 20,
+	Channel21 = 21,
+	Channel22 = 22,
+	Channel23 = 23,
+	Channel24 = 24,
+	Channel25 = 25,
+	Channel26 = 26,
+	Channel27 = 27,
+	Channel28 = 28,
+	Channel29 = 29,
+	Channel30 = 30,
+	Channel31 = 31,
+}
+
+/// The index of the `USER` register for each event user
+#[derive(Clone, Copy)]
+pub enum User {
+	RTC_TAMPER = 0,
+
+	PORT_EV0 = 1,
+	PORT_EV1 = 2,
+	PORT_EV2 = 3,
+	PORT_EV3 = 4,
+
+	DMAC_CH0 = 5,
+	DMAC_CH1 = 6,
+	DMAC_CH2 = 7,
+	DMAC_CH3 = 8,
+	DMAC_CH4 = 9,
+	DMAC_CH5 = 10,
+	DMAC_CH6 = 11,
+	DMAC_CH7 = 12,
+
+	CM4_TRACE_START = 13,
+	CM4_TRACE_STOP = 14,
+	CM4_TRACE_TRIG = 15,
+
+	TCC0_EV0 = 16,
+	TCC0_EV1 = 17,
+	TCC0_MC0 = 18,
+	TCC0_MC1 = 19,
+	TCC0_MC2 = 20,
+	TCC0_MC3 = 21,
+	TCC0_MC4 = 22,
+	TCC0_MC5 = 23,
+
+	TCC1_EV0 = 24,
+	TCC1_EV1 = 25,
+	TCC1_MC0 = 26,
+	TCC1_MC1 = 27,
+	TCC1_MC2 = 28,
+	TCC1_MC3 = 29,
+
+	TCC2_EV0 = 30,
+	TCC2_EV1 = 31,
+	TCC2_MC0 = 32,
+	TCC2_MC1 = 33,
+	TCC2_MC2 = 34,
+
+	TCC3_EV0 = 35,
+	TCC3_EV1 = 36,
+	TCC3_MC0 = 37,
+	TCC3_MC1 = 38,
+
+	TCC4_EV0 = 39,
+	TCC4_EV1 = 40,
+	TCC4_MC0 = 41,
+	TCC4_MC1 = 42,
+
+	TC0 = 43,
+	TC1 = 44,
+	TC2 = 45,
+	TC3 = 46,
+	TC4 = 47,
+	TC5 = 48,
+	TC6 = 49,
+	TC7 = 50,
+
+	PDEC_EVU0 = 51,
+	PDEC_EVU1 = 52,
+	PDEC_EVU2 = 53,
+
+	ADC0_START = 54,
+	ADC0_SYNC = 55,
+	ADC1_START = 56,
+	ADC1_SYNC = 57,
+
+	AC_SOC0 = 58,
+	AC_SOC1 = 59,
+
+	DAC_START0 = 60,
+	DAC_START1 = 61,
+
+	CCL_LUTIN0 = 62,
+	CCL_LUTIN1 = 63,
+	CCL_LUTIN2 = 64,
+	CCL_LUTIN3 = 65,
+}
+
+/// The `CHANNEL.EVGEN` value for each event generator
+#[derive(Clone, Copy)]
+pub enum Generator {
+	NONE = 0x00,
+
+	OSCCTRL_XOSC_FAIL0 = 0x01,
+	OSCCTRL_XOSC_FAIL1 = 0x02,
+	OSC32KCTRL_XOSC32K_FAIL = 0x03,
+
+	RTC_PER0 = 0x04,
+	RTC_PER1 = 0x05,
+	RTC_PER2 = 0x06,
+	RTC_PER3 = 0x07,
+	RTC_PER4 = 0x08,
+	RTC_PER5 = 0x09,
+	RTC_PER6 = 0x0A,
+	RTC_PER7 = 0x0B,
+	RTC_CMP0 = 0x0C,
+	RTC_CMP1 = 0x0D,
+	RTC_CMP2 = 0x0E,
+	RTC_CMP3 = 0x0F,
+	RTC_TAMPER = 0x10,
+	RTC_OVF = 0x11,
+
+	EXTINT0 = 0x12,
+	EXTINT1 = 0x13,
+	EXTINT2 = 0x14,
+	EXTINT3 = 0x15,
+	EXTINT4 = 0x16,
+	EXTINT5 = 0x17,
+	EXTINT6 = 0x18,
+	EXTINT7 = 0x19,
+	EXTINT8 = 0x1A,
+	EXTINT9 = 0x1B,
+	EXTINT10 = 0x1C,
+	EXTINT11 = 0x1D,
+	EXTINT12 = 0x1E,
+	EXTINT13 = 0x1F,
+	EXTINT14 = 0x20,
+	EXTINT15 = 0x21,
+
+	DMAC_CH0 = 0x22,
+	DMAC_CH1 = 0x23,
+	DMAC_CH2 = 0x24,
+	DMAC_CH3 = 0x25,
+
+	PAC_ACCERR = 0x26,
+
+	TCC0_OVF = 0x29,
+	TCC0_TRG = 0x2A,
+	TCC0_CNT = 0x2B,
+	TCC0_MC0 = 0x2C,
+	TCC0_MC1 = 0x2D,
+	TCC0_MC2 = 0x2E,
+	TCC0_MC3 = 0x2F,
+	TCC0_MC4 = 0x30,
+	TCC0_MC5 = 0x31,
+
+	TCC1_OVF = 0x32,
+	TCC1_TRG = 0x33,
+	TCC1_CNT = 0x34,
+	TCC1_MC0 = 0x35,
+	TCC1_MC1 = 0x36,
+	TCC1_MC2 = 0x37,
+	TCC1_MC3 = 0x38,
+
+	TCC2_OVF = 0x39,
+	TCC2_TRG = 0x3A,
+	TCC2_CNT = 0x3B,
+	TCC2_MC0 = 0x3C,
+	TCC2_MC1 = 0x3D,
+	TCC2_MC2 = 0x3E,
+
+	TCC3_OVF = 0x3F,
+	TCC3_TRG = 0x40,
+	TCC3_CNT = 0x41,
+	TCC3_MC0 = 0x42,
+	TCC3_MC1 = 0x43,
+
+	TCC4_OVF = 0x44,
+	TCC4_TRG = 0x45,
+	TCC4_CNT = 0x46,
+	TCC4_MC0 = 0x47,
+	TCC4_MC1 = 0x48,
+
+	TC0_OVF = 0x49,
+	TC0_MC0 = 0x4A,
+	TC0_MC1 = 0x4B,
+	TC1_OVF = 0x4C,
+	TC1_MC0 = 0x4D,
+	TC1_MC1 = 0x4E,
+	TC2_OVF = 0x4F,
+	TC2_MC0 = 0x50,
+	TC2_MC1 = 0x51,
+	TC3_OVF = 0x52,
+	TC3_MC0 = 0x53,
+	TC3_MC1 = 0x54,
+	TC4_OVF = 0x55,
+	TC4_MC0 = 0x56,
+	TC4_MC1 = 0x57,
+	TC5_OVF = 0x58,
+	TC5_MC0 = 0x59,
+	TC5_MC1 = 0x5A,
+	TC6_OVF = 0x5B,
+	TC6_MC0 = 0x5C,
+	TC6_MC1 = 0x5D,
+	TC7_OVF = 0x5E,
+	TC7_MC0 = 0x5F,
+	TC7_MC1 = 0x60,
+
+	PDEC_OVF = 0x61,
+	PDEC_ERR = 0x62,
+	PDEC_DIR = 0x63,
+	PDEC_VLC = 0x64,
+	PDEC_MC0 = 0x65,
+	PDEC_MC1 = 0x66,
+
+	ADC0_RESRDY = 0x67,
+	ADC0_WINMON = 0x68,
+	ADC1_RESRDY = 0x69,
+	ADC1_WINMON = 0x6A,
+
+	AC_COMP0 = 0x6B,
+	AC_COMP1 = 0x6C,
+	AC_WIN0 = 0x6D,
+
+	DAC_EMPTY0 = 0x6E,
+	DAC_EMPTY1 = 0x6F,
+	DAC_RESRDY0 = 0x70,
+	DAC_RESRDY1 = 0x71,
+
+	GMAC_TSU_CMP = 0x72,
+
+	TRNG_READY = 0x73,
+
+	CCL_LUTOUT0 = 0x74,
+	CCL_LUTOUT1 = 0x75,
+	CCL_LUTOUT2 = 0x76,
+	CCL_LUTOUT3 = 0x77,
+}
+
+pub enum EdgeSelector {
+	Rising,
+	Falling,
+	Both,
+}
+
+impl Into<evsys::channel::EDGSEL_A> for EdgeSelector {
+	fn into(self) -> evsys::channel::EDGSEL_A {
+		match self {
+			EdgeSelector::Rising => evsys::channel::EDGSEL_A::RISING_EDGE,
+			EdgeSelector::Falling => evsys::channel::EDGSEL_A::FALLING_EDGE,
+			EdgeSelector::Both => evsys::channel::EDGSEL_A::BOTH_EDGES,
+		}
+	}
+}
+
+/// The path an event takes through its channel. The synchronous and
+/// resynchronized paths need the generic clock of the channel, for example
+/// `GenericClockController::evch0` for `Channel0`, so only channels
+/// 0-11 can use them.
+pub enum Path {
+	Asynchronous,
+	Synchronous(EdgeSelector),
+	Resynchronized(EdgeSelector),
+}
+
+/// Something that can be published on an event channel. Peripheral drivers
+/// hand these out once they have enabled the matching event output.
+pub trait EventGenerator {
+	fn event_generator(&self) -> Generator;
+}
+
+/// Something that can be subscribed to an event channel. Peripheral drivers
+/// hand these out once they have enabled the matching event input.
+pub trait EventUser {
+	fn event_user(&self) -> User;
+}
+
+/// An event output that the driver of peripheral `P` has enabled, handed
+/// out by methods like `TimerCounter::overflow_event`.
+pub struct GeneratorHandle<P> {
+	generator: Generator,
+	_peripheral: PhantomData<P>,
+}
+
+impl<P> GeneratorHandle<P> {
+	pub(crate) fn new(generator: Generator) -> Self {
+		Self {
+			generator,
+			_peripheral: PhantomData,
+		}
+	}
+}
+
+impl<P> EventGenerator for GeneratorHandle<P> {
+	fn event_generator(&self) -> Generator {
+		self.generator
+	}
+}
+
+/// An event input that the driver of peripheral `P` has enabled, handed out
+/// by methods like `Adc::start_conversion_event`.
+pub struct UserHandle<P> {
+	user: User,
+	_peripheral: PhantomData<P>,
+}
+
+impl<P> UserHandle<P> {
+	pub(crate) fn new(user: User) -> Self {
+		Self {
+			user,
+			_peripheral: PhantomData,
+		}
+	}
+}
+
+impl<P> EventUser for UserHandle<P> {
+	fn event_user(&self) -> User {
+		self.user
+	}
+}
+
+impl EventSys {
+	pub fn init(mclk: &mut target_device::MCLK, evsys: target_device::EVSYS) -> Self {
+		mclk.apbbmask.modify(|_, w| w.evsys_().set_bit());
+
+		evsys.ctrla.write(|w| {
+			w.swrst().set_bit()
+		});
+
+		Self {
+			evsys,
+		}
+	}
+
+	fn registers(&self, channel: Channel) -> &ChannelRegisters {
+		let base = &self.evsys.channel0 as *const CHANNEL as *const ChannelRegisters;
+		unsafe { &*base.offset(channel as isize) }
+	}
+
+	/// Route `generator` to `user` through `channel`, taking `path`.
+	pub fn connect<G: EventGenerator, U: EventUser>(&mut self, channel: Channel, path: Path, generator: &G, user: &U) {
+		self.subscribe(channel, user);
+		self.publish(channel, path, generator);
+	}
+
+	pub fn subscribe<U: EventUser>(&mut self, channel: Channel, user: &U) {
+		self.evsys.user[user.event_user() as usize].write(|w| unsafe {
+			w.channel().bits(channel as u8 + 1)
+		});
+	}
+
+	/// Disconnect a user from whichever channel it was subscribed to
+	pub fn unsubscribe<U: EventUser>(&mut self, user: &U) {
+		self.evsys.user[user.event_user() as usize].write(|w| unsafe {
+			w.channel().bits(0)
+		});
+	}
+
+	pub fn publish<G: EventGenerator>(&mut self, channel: Channel, path: Path, generator: &G) {
+		self.registers(channel).channel.write(|w| unsafe {
+			match path {
+				Path::Asynchronous => {
+					w.path().asynchronous();
+				},
+				Path::Synchronous(edge_sel) => {
+					w.path().synchronous();
+					w.edgsel().variant(edge_sel.into());
+				},
+				Path::Resynchronized(edge_sel) => {
+					w.path().resynchronized();
+					w.edgsel().variant(edge_sel.into());
+				},
+			}
+
+			w.evgen().bits(generator.event_generator() as u8)
+		});
+	}
+
+	pub fn enable_event_detected_interrupt(&mut self, channel: Channel) {
+		self.registers(channel).chintenset.write(|w| {
+			w.evd().set_bit()
+		});
+	}
+
+	pub fn disable_event_detected_interrupt(&mut self, channel: Channel) {
+		self.registers(channel).chintenclr.write(|w| {
+			w.evd().set_bit()
+		});
+	}
+
+	pub fn enable_overrun_interrupt(&mut self, channel: Channel) {
+		self.registers(channel).chintenset.write(|w| {
+			w.ovr().set_bit()
+		});
+	}
+
+	/// Whether an event has been detected on this channel, clearing the flag
+	pub fn event_detected(&mut self, channel: Channel) -> bool {
+		let registers = self.registers(channel);
+
+		let detected = registers.chintflag.read().evd().bit_is_set();
+		if detected {
+			registers.chintflag.write(|w| {
+				w.evd().set_bit()
+			});
+		}
+
+		detected
+	}
+
+	/// Whether an event is still being handled by the users of this channel
+	pub fn is_busy(&self, channel: Channel) -> bool {
+		self.registers(channel).chstatus.read().busych().bit_is_set()
+	}
+
+	pub fn trigger(&mut self, channel: Channel) {
+		self.evsys.swevt.write(|w| unsafe {
+			w.bits(1 << channel as u8)
+		});
+	}
+}
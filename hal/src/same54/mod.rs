@@ -2,6 +2,14 @@ pub mod calibration;
 pub mod clock;
 pub mod sercom;
 pub mod timer;
+pub mod dmac;
+pub mod events;
+pub mod eic;
+pub mod rtc;
+pub mod capture;
+pub mod pdec;
+pub mod dac;
+pub mod ac;
 pub mod trng;
 
 #[cfg(feature = "unproven")]
@@ -2,10 +2,17 @@ use crate::gpio::{Pa1, Pa5, Pa7, Pa9, Pa11, Pa13, Pa15, Pa17, Pa19, Pb31, PfE};
 use crate::gpio::{Pa23, Pa25, Pb9, Pb11, Pb13, Pb15, Pb3, Pb17, Pa31, Pa21, Pb23, Pb1};
 
 use crate::clock;
+use crate::dmac::{
+    self,
+    channel::{Interrupts, Settings, TriggerSource},
+    transfer::{ReadBuffer, WriteBuffer},
+    Transfer,
+};
+use crate::events;
 use crate::timer::TimerParams;
 use crate::time::Hertz;
-use crate::hal::PwmPin;
-use crate::target_device::{TC0, TC1, TC2, TC3, TC4, TC5, TC6, TC7, MCLK};
+use crate::hal::{Pwm, PwmPin};
+use crate::target_device::{tcc0, TC0, TC1, TC2, TC3, TC4, TC5, TC6, TC7, TCC0, TCC1, TCC2, TCC3, TCC4, MCLK};
 
 pub enum TC0Pinout {
     Pa5(Pa5<PfE>),
@@ -192,3 +199,636 @@ pwm! {
     Pwm6: (TC6, TC6Pinout, Tc6Tc7Clock, apbdmask, tc6_, Pwm6Wrapper),
     Pwm7: (TC7, TC7Pinout, Tc6Tc7Clock, apbdmask, tc7_, Pwm7Wrapper),
 }
+
+// Timer/Counter for Control Applications (TCCx)
+
+pub enum TccChannel {
+    _0,
+    _1,
+    _2,
+    _3,
+    _4,
+    _5,
+}
+
+/// The channels dead time can be inserted on, each drives WO[n] and WO[n + 4]
+pub enum DeadTimeChannel {
+    _0,
+    _1,
+    _2,
+    _3,
+}
+
+/// How the counter runs within a period
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Alignment {
+    /// Single-slope PWM, the counter counts up to the period and wraps
+    Edge,
+    /// Dual-slope PWM, the counter counts up to the period and back down so
+    /// the pulses are centered in the period
+    Center,
+}
+
+/// A recoverable fault, fed by the match or capture event input with the
+/// same index. The TCC resumes once the fault input goes away.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RecoverableFault {
+    A,
+    B,
+}
+
+/// A non-recoverable fault, fed by the timer event input with the same
+/// index. The outputs stay in their fault state until the fault is cleared.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NonRecoverableFault {
+    Input0,
+    Input1,
+}
+
+/// What the counter does while a recoverable fault is active
+pub type FaultHalt = tcc0::fctrla::HALT_A;
+
+/// Extra resolution from dithering. The low 4, 5 or 6 bits of the period
+/// and duty values count the periods out of 16, 32 or 64 that are one tick
+/// longer, and the rest is the usual count in ticks.
+pub type Dithering = tcc0::ctrla::RESOLUTION_A;
+
+/// A buffered compare register of a TCC, the destination of the DMA
+/// transfers started by `feed_duty_dma`
+pub struct DutyRegister {
+    address: usize,
+}
+
+unsafe impl WriteBuffer for DutyRegister {
+    type Beat = u32;
+
+    fn dma_ptr(&mut self) -> *mut u32 {
+        self.address as *mut u32
+    }
+
+    fn incrementing(&self) -> bool {
+        false
+    }
+
+    fn buffer_len(&self) -> usize {
+        1
+    }
+}
+
+/// Duty values being fed to a TCC channel by DMA, as returned by
+/// `feed_duty_dma`.
+pub struct PwmDutyDma<PWM, Ch, B> {
+    pwm: PWM,
+    transfer: Transfer<Ch, B, DutyRegister>,
+}
+
+impl<PWM, Ch, B> PwmDutyDma<PWM, Ch, B>
+where
+    Ch: dmac::Channel,
+{
+    /// Whether the DMAC has written the last duty value, which takes effect
+    /// at the next update.
+    pub fn is_complete(&self) -> bool {
+        self.transfer.is_complete()
+    }
+
+    /// Block until the last duty value has been written, then release the
+    /// PWM, channel and buffer. If the DMAC stopped with an error the duty
+    /// stays at the last value written.
+    pub fn wait(self) -> Result<(PWM, Ch, B), (dmac::transfer::Error, PWM, Ch, B)> {
+        match self.transfer.wait() {
+            Ok((channel, buffer, _)) => Ok((self.pwm, channel, buffer)),
+            Err((error, channel, buffer, _)) => Err((error, self.pwm, channel, buffer)),
+        }
+    }
+
+    /// Stop feeding duty values, then release the PWM, channel and buffer.
+    /// The duty stays at the last value written.
+    pub fn stop(self) -> (PWM, Ch, B) {
+        let (channel, buffer, _) = self.transfer.stop();
+
+        (self.pwm, channel, buffer)
+    }
+}
+
+fn dither_bits(resolution: u8) -> u32 {
+    match resolution {
+        1 => 4,
+        2 => 5,
+        3 => 6,
+        _ => 0,
+    }
+}
+
+fn tcc_divider(prescaler: u8) -> u32 {
+    match prescaler {
+        0 => 1,
+        1 => 2,
+        2 => 4,
+        3 => 8,
+        4 => 16,
+        5 => 64,
+        6 => 256,
+        _ => 1024,
+    }
+}
+
+macro_rules! pwm_tcc {
+    ($($TYPE:ident: ($TCC:ident, $clock:ident, $apmask:ident, $apbits:ident, $wrapper:ident, $ovf:ident, $fault_a:ident, $fault_b:ident, $fault_0:ident, $fault_1:ident, $channels:expr),)+) => {
+        $(
+
+pub struct $TYPE {
+    /// The frequency of the attached clock, not the period of the pwm.
+    /// Used to calculate the period of the pwm.
+    clock_freq: Hertz,
+    tcc: $TCC,
+}
+
+impl $TYPE {
+    pub fn new<F: Into<Hertz>> (
+        clock: &clock::$clock,
+        freq: F,
+        tcc: $TCC,
+        mclk: &mut MCLK,
+    ) -> Self {
+        let freq = freq.into();
+        {
+            let params = TimerParams::new(freq, clock.freq().0);
+            mclk.$apmask.modify(|_, w| w.$apbits().set_bit());
+            tcc.ctrla.write(|w| w.swrst().set_bit());
+            while tcc.syncbusy.read().swrst().bit_is_set() {}
+            tcc.ctrlbclr.write(|w| w.dir().set_bit() );
+            while tcc.syncbusy.read().ctrlb().bit_is_set() {}
+            tcc.ctrla.modify(|_, w| w.enable().clear_bit());
+            tcc.ctrla.modify(|_, w| {
+                match params.divider {
+                    1 => w.prescaler().div1(),
+                    2 => w.prescaler().div2(),
+                    4 => w.prescaler().div4(),
+                    8 => w.prescaler().div8(),
+                    16 => w.prescaler().div16(),
+                    64 => w.prescaler().div64(),
+                    256 => w.prescaler().div256(),
+                    1024 => w.prescaler().div1024(),
+                    _ => unreachable!(),
+                }
+            });
+            tcc.wave.write(|w| w.wavegen().npwm());
+            while tcc.syncbusy.read().wave().bit_is_set() {}
+            tcc.per().write(|w| unsafe { w.bits(params.cycles as u32) });
+            while tcc.syncbusy.read().per().bit_is_set() {}
+            tcc.ctrla.modify(|_, w| w.enable().set_bit());
+        }
+
+        Self {
+            clock_freq: clock.freq(),
+            tcc,
+        }
+    }
+
+    pub fn alignment(&self) -> Alignment {
+        if self.tcc.wave.read().wavegen().is_npwm() {
+            Alignment::Edge
+        } else {
+            Alignment::Center
+        }
+    }
+
+    /// Switch between single and dual-slope PWM, keeping the period. A
+    /// dual-slope period takes twice as many ticks, so the period register
+    /// is halved and the duties must be set again afterwards.
+    pub fn set_alignment(&mut self, alignment: Alignment) {
+        if self.alignment() == alignment {
+            return;
+        }
+
+        let bits = self.dither_bits();
+        let top = self.tcc.per().read().bits() >> bits;
+        let top = match alignment {
+            Alignment::Edge => top * 2,
+            Alignment::Center => top / 2,
+        };
+        let top = top << bits;
+
+        self.tcc.wave.modify(|_, w| match alignment {
+            Alignment::Edge => w.wavegen().npwm(),
+            // Update the buffered registers at the bottom of each period
+            Alignment::Center => w.wavegen().dsbottom(),
+        });
+        while self.tcc.syncbusy.read().wave().bit_is_set() {}
+        self.tcc.per().write(|w| unsafe { w.bits(top) });
+        while self.tcc.syncbusy.read().per().bit_is_set() {}
+    }
+
+    /// Write the duty of `channel` to its buffer register, it takes effect at
+    /// the next update so the current period isn't cut short. Panics if the
+    /// TCC doesn't have `channel`, TCC0 has 6 channels, TCC1 4, TCC2 3 and
+    /// TCC3 and TCC4 2.
+    pub fn set_duty_buffered(&mut self, channel: TccChannel, duty: u32) {
+        let index = channel as usize;
+        assert!(index < $channels, "channel not on this TCC");
+        self.tcc.ccbuf()[index].write(|w| unsafe { w.ccbuf().bits(duty) });
+    }
+
+    /// Write the period to its buffer register, it takes effect at the next
+    /// update. The prescaler isn't changed so the period must be reachable
+    /// with the current one.
+    pub fn set_period_buffered<P: Into<Hertz>>(&mut self, period: P) {
+        let period = period.into();
+        let divisor = tcc_divider(self.tcc.ctrla.read().prescaler().bits());
+        let ticks = self.clock_freq.0 / divisor / period.0.max(1);
+        let top = match self.alignment() {
+            Alignment::Edge => ticks.saturating_sub(1),
+            Alignment::Center => ticks / 2,
+        };
+
+        self.tcc.perbuf().write(|w| unsafe { w.perbuf().bits(top << self.dither_bits()) });
+    }
+
+    /// Hold back the buffered values at updates, so that several of them
+    /// can be written and then take effect together after `unlock_update`.
+    pub fn lock_update(&mut self) {
+        self.tcc.ctrlbset.write(|w| w.lupd().set_bit());
+        while self.tcc.syncbusy.read().ctrlb().bit_is_set() {}
+    }
+
+    /// Let the buffered values take effect at the next update.
+    pub fn unlock_update(&mut self) {
+        self.tcc.ctrlbclr.write(|w| w.lupd().set_bit());
+        while self.tcc.syncbusy.read().ctrlb().bit_is_set() {}
+    }
+
+    /// Copy the buffered values into the period and compare registers now
+    /// rather than at the next update.
+    pub fn force_update(&mut self) {
+        self.tcc.ctrlbset.write(|w| w.cmd().update());
+        while self.tcc.syncbusy.read().ctrlb().bit_is_set() {}
+    }
+
+    /// Feed the duty values in `duties` to the buffer register of `channel`
+    /// with DMA on `dma`, one value per period. The PWM, channel and buffer
+    /// are handed back by the returned transfer. Panics if the TCC doesn't
+    /// have `channel`, as for `set_duty_buffered`.
+    pub fn feed_duty_dma<Ch, B>(self, channel: TccChannel, dma: Ch, duties: B, settings: Settings, interrupts: Interrupts) -> PwmDutyDma<Self, Ch, B>
+    where
+        Ch: dmac::Channel,
+        B: ReadBuffer<Beat = u32>,
+    {
+        let index = channel as usize;
+        assert!(index < $channels, "channel not on this TCC");
+        let register = DutyRegister {
+            address: &self.tcc.ccbuf()[index] as *const _ as usize,
+        };
+
+        // Each overflow pulls the next duty, which is applied at the update
+        // that follows
+        let transfer = Transfer::start(
+            dma,
+            duties,
+            register,
+            TriggerSource::$ovf,
+            settings,
+            interrupts,
+        );
+
+        PwmDutyDma {
+            pwm: self,
+            transfer,
+        }
+    }
+
+    fn dither_bits(&self) -> u32 {
+        dither_bits(self.tcc.ctrla.read().resolution().bits())
+    }
+
+    /// Set a recoverable fault, returning the event user to subscribe to
+    /// the fault source. With `restart` the counter restarts when the fault
+    /// goes away, with `keep` the fault state lasts until the end of the
+    /// period. `filter` is the number of consecutive samples the fault
+    /// input must hold, up to 15.
+    pub fn recoverable_fault(&mut self, fault: RecoverableFault, halt: FaultHalt, restart: bool, keep: bool, filter: u8) -> events::UserHandle<$TCC> {
+        self.configure(|tcc| {
+            match fault {
+                RecoverableFault::A => {
+                    tcc.fctrla.write(|w| unsafe {
+                        w.src().enable();
+                        w.halt().variant(halt);
+                        w.restart().bit(restart);
+                        w.keep().bit(keep);
+                        w.filterval().bits(filter)
+                    });
+                    tcc.evctrl.modify(|_, w| w.mcei0().set_bit());
+                },
+                RecoverableFault::B => {
+                    tcc.fctrlb.write(|w| unsafe {
+                        w.src().enable();
+                        // FCTRLB has its own copy of the FCTRLA types
+                        w.halt().bits(halt.into());
+                        w.restart().bit(restart);
+                        w.keep().bit(keep);
+                        w.filterval().bits(filter)
+                    });
+                    tcc.evctrl.modify(|_, w| w.mcei1().set_bit());
+                },
+            }
+        });
+
+        events::UserHandle::new(match fault {
+            RecoverableFault::A => events::User::$fault_a,
+            RecoverableFault::B => events::User::$fault_b,
+        })
+    }
+
+    /// Set a non-recoverable fault, returning the event user to subscribe
+    /// to the fault source. While the fault is active output WO[n] is forced
+    /// to bit n of `levels` if bit n of `outputs` is set. `filter` is the
+    /// number of consecutive samples the fault input must hold, up to 15.
+    pub fn non_recoverable_fault(&mut self, input: NonRecoverableFault, outputs: u8, levels: u8, filter: u8) -> events::UserHandle<$TCC> {
+        self.configure(|tcc| {
+            // NREn are bits 0 to 7, NRVn bits 8 to 15 and FILTERVALn four
+            // bits each from bit 24
+            let filter_shift = match input {
+                NonRecoverableFault::Input0 => 24,
+                NonRecoverableFault::Input1 => 28,
+            };
+            tcc.drvctrl.modify(|r, w| unsafe {
+                let mut bits = r.bits() & !(0xf << filter_shift) & !0xffff;
+                bits |= outputs as u32;
+                bits |= (levels as u32) << 8;
+                bits |= ((filter & 0xf) as u32) << filter_shift;
+                w.bits(bits)
+            });
+
+            tcc.evctrl.modify(|_, w| match input {
+                NonRecoverableFault::Input0 => {
+                    w.tcei0().set_bit();
+                    w.evact0().fault()
+                },
+                NonRecoverableFault::Input1 => {
+                    w.tcei1().set_bit();
+                    w.evact1().fault()
+                },
+            });
+        });
+
+        events::UserHandle::new(match input {
+            NonRecoverableFault::Input0 => events::User::$fault_0,
+            NonRecoverableFault::Input1 => events::User::$fault_1,
+        })
+    }
+
+    pub fn is_recoverable_fault(&self, fault: RecoverableFault) -> bool {
+        let flags = self.tcc.intflag.read();
+        match fault {
+            RecoverableFault::A => flags.faulta().bit_is_set(),
+            RecoverableFault::B => flags.faultb().bit_is_set(),
+        }
+    }
+
+    /// Clear the fault flag, and resume the counter after a fault with the
+    /// `SW` halt action.
+    pub fn clear_recoverable_fault(&mut self, fault: RecoverableFault) {
+        match fault {
+            RecoverableFault::A => {
+                self.tcc.intflag.write(|w| w.faulta().set_bit());
+                self.tcc.status.write(|w| w.faulta().set_bit());
+            },
+            RecoverableFault::B => {
+                self.tcc.intflag.write(|w| w.faultb().set_bit());
+                self.tcc.status.write(|w| w.faultb().set_bit());
+            },
+        }
+    }
+
+    pub fn is_non_recoverable_fault(&self, input: NonRecoverableFault) -> bool {
+        let flags = self.tcc.intflag.read();
+        match input {
+            NonRecoverableFault::Input0 => flags.fault0().bit_is_set(),
+            NonRecoverableFault::Input1 => flags.fault1().bit_is_set(),
+        }
+    }
+
+    /// Clear the fault flag and release the outputs, which only works once
+    /// the fault input has gone away.
+    pub fn clear_non_recoverable_fault(&mut self, input: NonRecoverableFault) {
+        match input {
+            NonRecoverableFault::Input0 => {
+                self.tcc.intflag.write(|w| w.fault0().set_bit());
+                self.tcc.status.write(|w| w.fault0().set_bit());
+            },
+            NonRecoverableFault::Input1 => {
+                self.tcc.intflag.write(|w| w.fault1().set_bit());
+                self.tcc.status.write(|w| w.fault1().set_bit());
+            },
+        }
+    }
+
+    /// Interrupt when any fault is detected.
+    pub fn enable_fault_interrupt(&mut self) {
+        self.tcc.intenset.write(|w| {
+            w.faulta().set_bit();
+            w.faultb().set_bit();
+            w.fault0().set_bit();
+            w.fault1().set_bit()
+        });
+    }
+
+    pub fn disable_fault_interrupt(&mut self) {
+        self.tcc.intenclr.write(|w| {
+            w.faulta().set_bit();
+            w.faultb().set_bit();
+            w.fault0().set_bit();
+            w.fault1().set_bit()
+        });
+    }
+
+    /// The fault, event and output registers can only be written while the
+    /// TCC is disabled, it is enabled again afterwards if it was running.
+    fn configure<F: FnOnce(&$TCC)>(&mut self, f: F) {
+        let enabled = self.tcc.ctrla.read().enable().bit_is_set();
+
+        self.tcc.ctrla.modify(|_, w| w.enable().clear_bit());
+        while self.tcc.syncbusy.read().enable().bit_is_set() {}
+
+        f(&self.tcc);
+
+        if enabled {
+            self.tcc.ctrla.modify(|_, w| w.enable().set_bit());
+            while self.tcc.syncbusy.read().enable().bit_is_set() {}
+        }
+    }
+}
+
+impl Pwm for $TYPE {
+    type Channel = TccChannel;
+    type Time = Hertz;
+    type Duty = u32;
+
+    fn disable(&mut self, _channel: Self::Channel) {
+        self.tcc.ctrla.modify(|_, w| w.enable().clear_bit());
+    }
+
+    fn enable(&mut self, _channel: Self::Channel) {
+        self.tcc.ctrla.modify(|_, w| w.enable().set_bit());
+    }
+
+    fn get_period(&self) -> Self::Time {
+        let divisor = tcc_divider(self.tcc.ctrla.read().prescaler().bits());
+        let top = self.tcc.per().read().bits() >> self.dither_bits();
+        // A dual-slope period counts up to the top and back down
+        let ticks = match self.alignment() {
+            Alignment::Edge => top + 1,
+            Alignment::Center => top * 2,
+        };
+        Hertz(self.clock_freq.0 / divisor / ticks.max(1))
+    }
+
+    fn get_duty(&self, channel: Self::Channel) -> Self::Duty {
+        let cc = self.tcc.cc();
+        let duty: u32 = cc[channel as usize].read().cc().bits();
+        duty
+    }
+
+    fn get_max_duty(&self) -> Self::Duty {
+        let top = self.tcc.per().read().bits();
+        top
+    }
+
+    fn set_duty(&mut self, channel: Self::Channel, duty: Self::Duty) {
+        let cc = self.tcc.cc();
+        cc[channel as usize].write(|w| unsafe { w.cc().bits(duty) });
+    }
+
+    fn set_period<P>(&mut self, period: P)
+    where
+        P: Into<Self::Time>,
+    {
+        let period = period.into();
+        let params = TimerParams::new(period, self.clock_freq.0);
+        let cycles = match self.alignment() {
+            Alignment::Edge => params.cycles,
+            Alignment::Center => params.cycles / 2,
+        };
+        self.tcc.ctrla.modify(|_, w| w.enable().clear_bit());
+        self.tcc.ctrla.modify(|_, w| {
+            match params.divider {
+                1 => w.prescaler().div1(),
+                2 => w.prescaler().div2(),
+                4 => w.prescaler().div4(),
+                8 => w.prescaler().div8(),
+                16 => w.prescaler().div16(),
+                64 => w.prescaler().div64(),
+                256 => w.prescaler().div256(),
+                1024 => w.prescaler().div1024(),
+                _ => unreachable!(),
+            }
+        });
+        self.tcc.ctrla.modify(|_, w| w.enable().set_bit());
+        self.tcc.per().write(|w| unsafe { w.bits((cycles as u32) << self.dither_bits()) });
+        while self.tcc.syncbusy.read().per().bit() {}
+    }
+}
+
+)+}}
+
+/// Dead time insertion, only TCC0 and TCC1 have the waveform extension
+macro_rules! pwm_tcc_dead_time {
+    ($($TYPE:ident,)+) => {
+        $(
+
+impl $TYPE {
+    /// Drive WO[n] and WO[n + 4] from channel n as a complementary pair,
+    /// with `low_side` ticks between the high side turning off and the low
+    /// side turning on and `high_side` ticks the other way round. The dead
+    /// times are shared by all channels.
+    pub fn dead_time(&mut self, channel: DeadTimeChannel, low_side: u8, high_side: u8) {
+        self.configure(|tcc| {
+            tcc.wexctrl.modify(|r, w| unsafe {
+                // DTIENn are bits 8 to 11
+                w.bits(r.bits() | 1 << (8 + channel as u32));
+                w.dtls().bits(low_side);
+                w.dths().bits(high_side)
+            });
+        });
+    }
+
+    pub fn disable_dead_time(&mut self, channel: DeadTimeChannel) {
+        self.configure(|tcc| {
+            tcc.wexctrl.modify(|r, w| unsafe {
+                w.bits(r.bits() & !(1 << (8 + channel as u32)))
+            });
+        });
+    }
+}
+
+)+}}
+
+/// Dithering, on TCC0 and TCC1
+macro_rules! pwm_tcc_dithering {
+    ($($TYPE:ident,)+) => {
+        $(
+
+impl $TYPE {
+    /// Set the dithering resolution, keeping the period. The duties must be
+    /// set again afterwards, with the new number of fractional bits.
+    pub fn set_dithering(&mut self, dithering: Dithering) {
+        let top = self.tcc.per().read().bits() >> self.dither_bits();
+        let bits = dither_bits(dithering.into());
+
+        self.configure(|tcc| {
+            tcc.ctrla.modify(|_, w| w.resolution().variant(dithering));
+        });
+
+        self.tcc.per().write(|w| unsafe { w.bits(top << bits) });
+        while self.tcc.syncbusy.read().per().bit_is_set() {}
+    }
+}
+
+)+}}
+
+/// Pattern generation, on TCC0 and TCC1
+macro_rules! pwm_tcc_pattern {
+    ($($TYPE:ident,)+) => {
+        $(
+
+impl $TYPE {
+    /// Override WO[n] with bit n of `levels` if bit n of `outputs` is set.
+    /// The pattern is buffered and takes effect at the next update, so a
+    /// sequence of patterns can be stepped through once per period.
+    pub fn set_pattern(&mut self, outputs: u8, levels: u8) {
+        self.tcc.pattbuf.write(|w| unsafe {
+            // PGEBn are bits 0 to 7 and PGVBn bits 8 to 15
+            w.bits((outputs as u16) | (levels as u16) << 8)
+        });
+    }
+
+    /// Stop overriding the outputs at the next update.
+    pub fn clear_pattern(&mut self) {
+        self.tcc.pattbuf.write(|w| unsafe { w.bits(0) });
+    }
+}
+
+)+}}
+
+pwm_tcc! {
+    Tcc0Pwm: (TCC0, Tcc0Tcc1Clock, apbbmask, tcc0_, Tcc0PwmWrapper, Tcc0Ovf, TCC0_MC0, TCC0_MC1, TCC0_EV0, TCC0_EV1, 6),
+    Tcc1Pwm: (TCC1, Tcc0Tcc1Clock, apbbmask, tcc1_, Tcc1PwmWrapper, Tcc1Ovf, TCC1_MC0, TCC1_MC1, TCC1_EV0, TCC1_EV1, 4),
+    Tcc2Pwm: (TCC2, Tcc2Tcc3Clock, apbcmask, tcc2_, Tcc2PwmWrapper, Tcc2Ovf, TCC2_MC0, TCC2_MC1, TCC2_EV0, TCC2_EV1, 3),
+    Tcc3Pwm: (TCC3, Tcc2Tcc3Clock, apbcmask, tcc3_, Tcc3PwmWrapper, Tcc3Ovf, TCC3_MC0, TCC3_MC1, TCC3_EV0, TCC3_EV1, 2),
+    Tcc4Pwm: (TCC4, Tcc4Clock, apbdmask, tcc4_, Tcc4PwmWrapper, Tcc4Ovf, TCC4_MC0, TCC4_MC1, TCC4_EV0, TCC4_EV1, 2),
+}
+
+pwm_tcc_dead_time! {
+    Tcc0Pwm,
+    Tcc1Pwm,
+}
+
+pwm_tcc_dithering! {
+    Tcc0Pwm,
+    Tcc1Pwm,
+}
+
+pwm_tcc_pattern! {
+    Tcc0Pwm,
+    Tcc1Pwm,
+}
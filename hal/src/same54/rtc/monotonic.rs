@@ -0,0 +1,136 @@
+//! A free running 64 bit clock built on the RTC.
+//!
+//! The RTC counts the low 32 bits in `Count32Mode` and its overflow interrupt
+//! counts the high 32 bits, so the clock doesn't wrap. It ticks at 1.024kHz
+//! and keeps counting in standby.
+//!
+//! ```ignore
+//! let mut mono = Monotonic::new(&mut mclk, rtc);
+//! mono.schedule(Monotonic::now() + Duration::from_millis(500, mono.frequency()));
+//!
+//! // In the RTC interrupt handler
+//! if mono.on_interrupt() {
+//!     // The scheduled instant has passed
+//! }
+//! ```
+use core::sync::atomic::{AtomicU32, Ordering};
+
+use cortex_m::interrupt;
+use cortex_m::peripheral::NVIC;
+
+use crate::{
+	target_device,
+	time::{Hertz, Instant},
+};
+
+use super::{Count32Mode, Prescaler, RTC};
+
+// The high 32 bits of the clock, counted by `on_interrupt`
+static OVERFLOWS: AtomicU32 = AtomicU32::new(0);
+
+pub struct Monotonic {
+	rtc: RTC<Count32Mode>,
+	freq: Hertz,
+	deadline: Option<Instant>,
+}
+
+impl Monotonic {
+	/// Start the clock from zero. The RTC interrupt must be unmasked in the
+	/// NVIC and call `on_interrupt`.
+	pub fn new(mclk: &mut target_device::MCLK, rtc: target_device::RTC) -> Self {
+		let freq = Hertz(1_024);
+		let mut rtc = RTC::count32(mclk, rtc, Prescaler::DIV1, false);
+
+		OVERFLOWS.store(0, Ordering::SeqCst);
+		rtc.clear_overflow();
+		rtc.enable_overflow_interrupt();
+
+		Monotonic {
+			rtc,
+			freq,
+			deadline: None,
+		}
+	}
+
+	/// The frequency that the clock ticks at.
+	pub fn frequency(&self) -> Hertz {
+		self.freq
+	}
+
+	/// The current time. This doesn't need the `Monotonic` so that it can be
+	/// called from any context once the clock has started.
+	pub fn now() -> Instant {
+		let mode = unsafe { &*target_device::RTC::ptr() }.mode0();
+
+		let read_count = || {
+			while mode.syncbusy.read().count().bit_is_set() {
+				cortex_m::asm::nop();
+			}
+			mode.count.read().bits()
+		};
+
+		// The overflow interrupt can't run between reading the two halves
+		interrupt::free(|_| {
+			let mut high = OVERFLOWS.load(Ordering::SeqCst);
+			let mut low = read_count();
+
+			// An overflow that hasn't been counted yet, the count has wrapped
+			// so read it again in case it was read before
+			if mode.intflag.read().ovf().bit_is_set() {
+				high += 1;
+				low = read_count();
+			}
+
+			Instant(((high as u64) << 32) | low as u64)
+		})
+	}
+
+	/// Interrupt at `instant`, or as soon as possible if it has passed.
+	/// Replaces any instant that is already scheduled.
+	pub fn schedule(&mut self, instant: Instant) {
+		self.deadline = Some(instant);
+
+		self.rtc.set_compare(instant.0 as u32);
+		self.rtc.clear_compare();
+		self.rtc.enable_compare_interrupt();
+
+		// The count may have passed the compare value before it was written
+		if Self::now() >= instant {
+			NVIC::pend(target_device::Interrupt::RTC_INTREQ);
+		}
+	}
+
+	/// Stop waiting for the scheduled instant.
+	pub fn cancel(&mut self) {
+		self.deadline = None;
+		self.rtc.disable_compare_interrupt();
+		self.rtc.clear_compare();
+	}
+
+	/// Service the RTC interrupt, returns true once the scheduled instant
+	/// has passed. The compare value only matches the low 32 bits, so the
+	/// interrupt can fire early for instants far in the future.
+	pub fn on_interrupt(&mut self) -> bool {
+		interrupt::free(|_| {
+			if self.rtc.is_overflow() {
+				OVERFLOWS.store(OVERFLOWS.load(Ordering::SeqCst) + 1, Ordering::SeqCst);
+				self.rtc.clear_overflow();
+			}
+		});
+
+		self.rtc.clear_compare();
+
+		match self.deadline {
+			Some(instant) if Self::now() >= instant => {
+				self.cancel();
+				true
+			},
+			_ => false,
+		}
+	}
+
+	/// Stop the clock and release the RTC.
+	pub fn free(self) -> target_device::RTC {
+		self.rtc.free()
+	}
+}